@@ -1,10 +1,37 @@
 use cosmwasm_schema::write_api;
-use counting_contract::msg::{InstantiateMsg, ExecMsg, QueryMsg};
+use counting_contract::msg::{ExecMsg, InstantiateMsg, QueryMsg, SudoMsg};
 
-fn main(){
-    write_api!{
+fn main() {
+    write_api! {
         instantiate: InstantiateMsg,
         execute: ExecMsg,
         query: QueryMsg,
+        sudo: SudoMsg,
     }
-}
\ No newline at end of file
+
+    // `write_api!` clears the schema directory, so the factory is written after it
+    #[cfg(feature = "factory")]
+    write_factory_api();
+}
+
+#[cfg(feature = "factory")]
+fn write_factory_api() {
+    use std::env::current_dir;
+    use std::fs::File;
+
+    use cosmwasm_schema::generate_api;
+    use counting_contract::factory;
+
+    let factory = generate_api! {
+        name: "counting_contract_factory",
+        instantiate: factory::msg::InstantiateMsg,
+        execute: factory::msg::ExecMsg,
+        query: factory::msg::QueryMsg,
+    }
+    .render();
+    let path = current_dir()
+        .unwrap()
+        .join("schema")
+        .join("counting_contract_factory.json");
+    factory.to_writer(File::create(path).unwrap()).unwrap();
+}
@@ -1,51 +1,604 @@
-use cosmwasm_std::{Coin, DepsMut, MessageInfo, Response, StdResult};
-use cw2::{get_contract_version, set_contract_version};
-use cw_storage_plus::Item;
+use cosmwasm_std::{
+    to_json_binary, Addr, BlockInfo, Coin, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+    Response, StdResult, Storage, Timestamp, Uint128, WasmMsg,
+};
+use cw2::{get_contract_version, set_contract_version, CONTRACT};
+use cw_storage_plus::{Bound, Item};
+use cw_utils::{Duration, Expiration};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     error::ContractError,
-    msg::Parent,
-    state::{ParentDonation, State, OWNER, PARENT_DONATION, STATE},
+    msg::{
+        CheckpointPruning, ExecMsg, FeeConfig, InstantiateMsg, Parent, ParentResp, ProposalStatus,
+        QueryMsg, VestingSchedule,
+    },
+    state::{
+        CounterState, Fee, ForwardTime, ParentDonation, Payout, Proposal, CHECKPOINT_PRUNING,
+        COUNTERS, COUNTER_CHECKPOINTS, DEFAULT_COUNTER_ID, DEFAULT_PARENT_DEPTH_LIMIT, DONATIONS,
+        ESCROWED, FEE, FEE_ADMIN, FORWARDED, IBC_HOOKS, LAST_FORWARD, MIGRATED_BALANCE,
+        NAMED_BALANCE, OWED, PARENT_DEPTH_LIMIT, PRUNED_CHECKPOINTS, TOTAL_OWED, TOTAL_WEIGHT,
+        VESTING, VOTING_WEIGHT, WITHDRAWN,
+    },
 };
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const MAX_MEMO_LENGTH: usize = 256;
+const MAX_COUNTER_ID_LENGTH: usize = 64;
+const DEFAULT_PAGE_LIMIT: u32 = 10;
+const MAX_PAGE_LIMIT: u32 = 30;
+const MAX_PRUNED_CHECKPOINTS: usize = 10;
 
 pub fn instantiate(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    counter: u64,
-    minimal_donation: Coin,
-    parent: Option<Parent>,
-) -> StdResult<Response> {
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let InstantiateMsg {
+        counter,
+        minimal_donation,
+        parent,
+        parent_depth_limit,
+        vesting,
+        fee,
+        fee_admin,
+        owner,
+        ibc_hooks,
+    } = msg;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    STATE.save(
+    PARENT_DEPTH_LIMIT.save(
+        deps.storage,
+        &parent_depth_limit.unwrap_or(DEFAULT_PARENT_DEPTH_LIMIT),
+    )?;
+    if let Some(vesting) = vesting {
+        validate_vesting(&vesting)?;
+        VESTING.save(deps.storage, &vesting)?;
+    }
+    if let Some(fee) = fee {
+        let fee = validate_fee(deps.as_ref(), fee)?;
+        FEE.save(deps.storage, &fee)?;
+    }
+    if let Some(fee_admin) = fee_admin {
+        FEE_ADMIN.save(deps.storage, &deps.api.addr_validate(&fee_admin)?)?;
+    }
+    if ibc_hooks {
+        IBC_HOOKS.save(deps.storage, &true)?;
+    }
+
+    let owner = match owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+
+    let mut resp = Response::new();
+
+    let parent = parent
+        .map(|parent| parent_donation(deps.as_ref(), &env, parent))
+        .transpose()?;
+    if let Some(parent) = parent.as_ref().filter(|parent| parent.remote.is_none()) {
+        resp = resp.add_message(WasmMsg::Execute {
+            contract_addr: parent.address.to_string(),
+            msg: to_json_binary(&ExecMsg::RegisterChild {})?,
+            funds: vec![],
+        });
+    }
+
+    COUNTERS.save(
         deps.storage,
-        &State {
-            counter: counter,
-            minimal_donation: minimal_donation,
-            donating_parent: parent.as_ref().map(|p| p.donating_period),
+        DEFAULT_COUNTER_ID,
+        &CounterState {
+            counter,
+            minimal_donation,
+            owner,
+            donating_parent: parent.as_ref().map(|p| p.donating_parent_period),
+            parent,
+            balance: vec![],
         },
     )?;
 
-    OWNER.save(deps.storage, &info.sender)?;
+    start_forward_interval(deps.storage, DEFAULT_COUNTER_ID, &env.block)?;
+    checkpoint_counter(deps.storage, DEFAULT_COUNTER_ID, env.block.height, counter)?;
 
-    if let Some(parent) = parent {
-        PARENT_DONATION.save(
-            deps.storage,
-            &ParentDonation {
-                address: deps.api.addr_validate(&parent.addr)?,
-                donating_parent_period: parent.donating_period,
-                part: parent.part,
-            },
-        )?;
+    Ok(resp)
+}
+
+/// Adds `coins` to the per-denom `total`, skipping empty amounts.
+pub fn add_coins(total: &mut Vec<Coin>, coins: &[Coin]) {
+    for coin in coins.iter().filter(|coin| !coin.amount.is_zero()) {
+        match total.iter_mut().find(|total| total.denom == coin.denom) {
+            Some(total) => total.amount += coin.amount,
+            None => total.push(coin.clone()),
+        }
     }
+}
 
-    Ok(Response::new())
+/// `coins` with amounts of the same denom added together and empty amounts dropped.
+pub fn merge_coins(coins: &[Coin]) -> Vec<Coin> {
+    let mut merged = vec![];
+    add_coins(&mut merged, coins);
+    merged
+}
+
+pub fn validate_vesting(schedule: &VestingSchedule) -> Result<(), ContractError> {
+    match schedule {
+        VestingSchedule::Linear { start, end } if start >= end => {
+            Err(ContractError::InvalidVesting {})
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Share of the retained donations that the vesting schedule has released at `now`.
+fn vested_fraction(schedule: &VestingSchedule, now: Timestamp) -> Decimal {
+    match schedule {
+        VestingSchedule::Linear { start, end } => {
+            if now <= *start {
+                Decimal::zero()
+            } else if now >= *end {
+                Decimal::one()
+            } else {
+                Decimal::from_ratio(now.nanos() - start.nanos(), end.nanos() - start.nanos())
+            }
+        }
+        VestingSchedule::Cliff { at } => {
+            if now >= *at {
+                Decimal::one()
+            } else {
+                Decimal::zero()
+            }
+        }
+    }
+}
+
+/// Amount of `denom` in `coins`, zero if there is none.
+pub fn amount_of(coins: &[Coin], denom: &str) -> Uint128 {
+    coins
+        .iter()
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .sum()
+}
+
+/// Whether `funds` contain at least `minimal_donation`. A zero minimum counts everything.
+pub fn is_counted(minimal_donation: &Coin, funds: &[Coin]) -> bool {
+    minimal_donation.amount.is_zero()
+        || funds.iter().any(|coin| {
+            coin.denom == minimal_donation.denom && coin.amount >= minimal_donation.amount
+        })
+}
+
+/// Contract balance without the funds held by named counters or subscription deposits.
+pub fn default_balance(deps: Deps, env: &Env) -> StdResult<Vec<Coin>> {
+    let mut held = NAMED_BALANCE.may_load(deps.storage)?.unwrap_or_default();
+    add_coins(
+        &mut held,
+        &ESCROWED.may_load(deps.storage)?.unwrap_or_default(),
+    );
+    let balance = deps
+        .querier
+        .query_all_balances(&env.contract.address)?
+        .into_iter()
+        .map(|mut coin| {
+            coin.amount = coin.amount.saturating_sub(amount_of(&held, &coin.denom));
+            coin
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect();
+
+    Ok(balance)
+}
+
+/// Amount per denom the owner can take out right now. That is the balance not owed to
+/// beneficiaries; with a vesting schedule it is further capped by the vested part of the
+/// retained donations minus what was already withdrawn. Donations are retained once forwards
+/// to the parent, fees and beneficiary credits are taken out of them, so the retained total is
+/// what the default counter holds for the owner plus what was withdrawn from it.
+pub fn withdrawable(deps: Deps, env: &Env) -> StdResult<Vec<Coin>> {
+    let owed = TOTAL_OWED.may_load(deps.storage)?.unwrap_or_default();
+    let balance: Vec<_> = default_balance(deps, env)?
+        .into_iter()
+        .map(|mut coin| {
+            coin.amount = coin.amount.saturating_sub(amount_of(&owed, &coin.denom));
+            coin
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect();
+
+    let vesting = match VESTING.may_load(deps.storage)? {
+        Some(vesting) => vesting,
+        None => return Ok(balance),
+    };
+
+    let fraction = vested_fraction(&vesting, env.block.time);
+    let withdrawn = WITHDRAWN.may_load(deps.storage)?.unwrap_or_default();
+
+    let withdrawable = balance
+        .into_iter()
+        .map(|mut coin| {
+            let withdrawn = amount_of(&withdrawn, &coin.denom);
+            let vested = (coin.amount + withdrawn) * fraction;
+            coin.amount = coin.amount.min(vested.saturating_sub(withdrawn));
+            coin
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect();
+
+    Ok(withdrawable)
+}
+
+/// Makes sure every coin in `amount` fits into what can be withdrawn right now and records it
+/// as withdrawn.
+pub fn take_withdrawable(deps: DepsMut, env: &Env, amount: &[Coin]) -> Result<(), ContractError> {
+    let withdrawable = withdrawable(deps.as_ref(), env)?;
+    for coin in &merge_coins(amount) {
+        let available = amount_of(&withdrawable, &coin.denom);
+        if coin.amount > available {
+            return Err(ContractError::ExceedsVested {
+                available: Coin {
+                    denom: coin.denom.clone(),
+                    amount: available,
+                },
+            });
+        }
+    }
+
+    let mut withdrawn = WITHDRAWN.may_load(deps.storage)?.unwrap_or_default();
+    add_coins(&mut withdrawn, amount);
+    WITHDRAWN.save(deps.storage, &withdrawn)?;
+
+    Ok(())
+}
+
+/// Credits `funds` to the beneficiaries' claimable balances according to their shares, with
+/// the rounding leftovers going to the remainder address.
+pub fn credit_beneficiaries(deps: DepsMut, payout: &Payout, funds: &[Coin]) -> StdResult<()> {
+    let mut remainder = funds.to_vec();
+    let mut credits = vec![];
+    for beneficiary in &payout.beneficiaries {
+        let share: Vec<_> = funds
+            .iter()
+            .map(|coin| Coin {
+                denom: coin.denom.clone(),
+                amount: coin.amount * beneficiary.share,
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+        remainder = sub_coins(&remainder, &share);
+        credits.push((&beneficiary.addr, share));
+    }
+    credits.push((&payout.remainder, remainder));
+
+    let mut total_owed = TOTAL_OWED.may_load(deps.storage)?.unwrap_or_default();
+    for (addr, credit) in credits {
+        if credit.is_empty() {
+            continue;
+        }
+
+        OWED.update(deps.storage, addr, |owed| -> StdResult<_> {
+            let mut owed = owed.unwrap_or_default();
+            add_coins(&mut owed, &credit);
+            Ok(owed)
+        })?;
+        add_coins(&mut total_owed, &credit);
+    }
+    TOTAL_OWED.save(deps.storage, &total_owed)?;
+
+    Ok(())
+}
+
+/// Fails if beneficiaries are owed more of any denom than the default counter holds.
+pub fn check_owed_invariant(deps: Deps, env: &Env) -> Result<(), ContractError> {
+    let balance = default_balance(deps, env)?;
+    let owed = TOTAL_OWED.may_load(deps.storage)?.unwrap_or_default();
+
+    for coin in owed {
+        if coin.amount > amount_of(&balance, &coin.denom) {
+            return Err(ContractError::OwedExceedsBalance { owed: coin });
+        }
+    }
+
+    Ok(())
+}
+
+/// Records `counter` as the value of counter `id` at the end of block `height`, then drops
+/// changelog entries of the counter the pruning setting no longer needs.
+pub fn checkpoint_counter(
+    storage: &mut dyn Storage,
+    id: &str,
+    height: u64,
+    counter: u64,
+) -> StdResult<()> {
+    COUNTER_CHECKPOINTS.save(storage, id, &counter, height)?;
+
+    let blocks = match CHECKPOINT_PRUNING.may_load(storage)? {
+        Some(CheckpointPruning::KeepBlocks { blocks }) => blocks,
+        Some(CheckpointPruning::Never) | None => return Ok(()),
+    };
+
+    // An entry holds the value before its block, so the values within the kept window only
+    // need entries from the window on
+    let cutoff = height.saturating_sub(blocks);
+    let stale = COUNTER_CHECKPOINTS
+        .changelog()
+        .prefix(id)
+        .keys(
+            storage,
+            None,
+            Some(Bound::exclusive(cutoff)),
+            Order::Ascending,
+        )
+        .take(MAX_PRUNED_CHECKPOINTS)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    if let Some(&last) = stale.last() {
+        for height in stale {
+            COUNTER_CHECKPOINTS
+                .changelog()
+                .remove(storage, (id, height));
+        }
+        PRUNED_CHECKPOINTS.save(storage, id, &last)?;
+    }
+
+    Ok(())
+}
+
+pub fn validate_fee(deps: Deps, fee: FeeConfig) -> Result<Fee, ContractError> {
+    if fee.rate > Decimal::one() {
+        return Err(ContractError::InvalidFeeRate { rate: fee.rate });
+    }
+
+    Ok(Fee {
+        recipient: deps.api.addr_validate(&fee.recipient)?,
+        rate: fee.rate,
+    })
+}
+
+/// Adds `amount` donated by `donor` to the default counter to their voting weight.
+pub fn add_voting_weight(
+    storage: &mut dyn Storage,
+    height: u64,
+    donor: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+
+    VOTING_WEIGHT.update(storage, donor, height, |weight| -> StdResult<_> {
+        Ok(weight.unwrap_or_default() + amount)
+    })?;
+    TOTAL_WEIGHT.update(storage, height, |weight| -> StdResult<_> {
+        Ok(weight.unwrap_or_default() + amount)
+    })?;
+
+    Ok(())
+}
+
+/// Works out whether an open proposal passed. Until it expires it only passes once enough
+/// yes votes are in that no later votes could change it, like cw3 threshold quorum voting.
+pub fn proposal_status(proposal: &Proposal, block: &BlockInfo) -> ProposalStatus {
+    if proposal.status != ProposalStatus::Open {
+        return proposal.status;
+    }
+
+    let votes = proposal.yes + proposal.no;
+    let quorum = !votes.is_zero() && votes >= proposal.total_weight * proposal.quorum;
+
+    if proposal.expires.is_expired(block) {
+        if quorum && proposal.yes >= votes * proposal.threshold {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        }
+    } else if quorum && proposal.yes >= proposal.total_weight * proposal.threshold {
+        ProposalStatus::Passed
+    } else {
+        ProposalStatus::Open
+    }
+}
+
+pub fn validate_counter_id(id: &str) -> Result<(), ContractError> {
+    if id.is_empty() || id.len() > MAX_COUNTER_ID_LENGTH {
+        return Err(ContractError::InvalidCounterId(format!(
+            "must be between 1 and {} bytes long",
+            MAX_COUNTER_ID_LENGTH
+        )));
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(ContractError::InvalidCounterId(
+            "only ASCII letters, digits, '_' and '-' are allowed".to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn validate_memo(memo: &str) -> Result<(), ContractError> {
+    if memo.len() > MAX_MEMO_LENGTH {
+        return Err(ContractError::InvalidMemo(format!(
+            "longer than {} bytes",
+            MAX_MEMO_LENGTH
+        )));
+    }
+    if memo.chars().any(char::is_control) {
+        return Err(ContractError::InvalidMemo(
+            "contains control characters".to_owned(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Subtracts `coins` from `total` per denom, dropping denoms that end up empty. Every coin in
+/// `coins` has to be covered by `total`.
+pub fn sub_coins(total: &[Coin], coins: &[Coin]) -> Vec<Coin> {
+    total
+        .iter()
+        .map(|total| {
+            let sub: Uint128 = coins
+                .iter()
+                .filter(|coin| coin.denom == total.denom)
+                .map(|coin| coin.amount)
+                .sum();
+            Coin {
+                denom: total.denom.clone(),
+                amount: total.amount - sub,
+            }
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect()
+}
+
+/// Validates a requested parent. Parents on this chain go through `check_parent_chain`, while
+/// remote ones can't be checked from here and are taken as given, once ibc-hooks support is
+/// enabled.
+pub fn parent_donation(
+    deps: Deps,
+    env: &Env,
+    parent: Parent,
+) -> Result<ParentDonation, ContractError> {
+    let address = match &parent.remote {
+        Some(_) if !cfg!(feature = "ibc") => return Err(ContractError::IbcDisabled {}),
+        Some(remote) => {
+            if !IBC_HOOKS.may_load(deps.storage)?.unwrap_or(false) {
+                return Err(ContractError::IbcHooksDisabled {});
+            }
+            if remote.timeout == 0 {
+                return Err(ContractError::InvalidTransferTimeout {});
+            }
+            Addr::unchecked(parent.addr)
+        }
+        None => {
+            let address = deps.api.addr_validate(&parent.addr)?;
+            check_parent_chain(deps, env, &address)?;
+            address
+        }
+    };
+
+    Ok(ParentDonation {
+        address,
+        donating_parent_period: parent.donating_period,
+        part: parent.part,
+        remote: parent.remote,
+        interval: parent.interval,
+    })
+}
+
+/// Starts a new parent donating interval of counter `id` at `block`.
+pub fn start_forward_interval(
+    storage: &mut dyn Storage,
+    id: &str,
+    block: &BlockInfo,
+) -> StdResult<()> {
+    LAST_FORWARD.save(
+        storage,
+        id,
+        &ForwardTime {
+            height: block.height,
+            time: block.time,
+        },
+    )
+}
+
+/// When counter `id` can next forward to a parent donated to every `interval`, `None` if it
+/// can right away.
+pub fn next_forward(
+    storage: &dyn Storage,
+    id: &str,
+    interval: Duration,
+) -> StdResult<Option<Expiration>> {
+    let next = LAST_FORWARD
+        .may_load(storage, id)?
+        .map(|last| match interval {
+            Duration::Height(blocks) => Expiration::AtHeight(last.height + blocks),
+            Duration::Time(seconds) => Expiration::AtTime(last.time.plus_seconds(seconds)),
+        });
+    Ok(next)
+}
+
+/// Whether counter `id` should forward to `parent` on the donation counted now. For parents
+/// donated to every number of donations, it counts `period` down and restarts it.
+pub fn forward_due(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    id: &str,
+    parent: &ParentDonation,
+    period: &mut u64,
+) -> StdResult<bool> {
+    if let Some(interval) = parent.interval {
+        return Ok(match next_forward(storage, id, interval)? {
+            Some(next) => next.is_expired(block),
+            None => true,
+        });
+    }
+
+    *period -= 1;
+    if *period == 0 {
+        *period = parent.donating_parent_period;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+pub fn record_forward(deps: DepsMut, env: &Env, parent: &Addr, funds: &[Coin]) -> StdResult<()> {
+    FORWARDED.update(deps.storage, parent, |stats| -> StdResult<_> {
+        let mut stats = stats.unwrap_or_default();
+        add_coins(&mut stats.forwarded, funds);
+        stats.count += 1;
+        stats.last_height = Some(env.block.height);
+        Ok(stats)
+    })?;
+
+    Ok(())
+}
+
+/// Makes sure `parent` is a counting contract and that following its parents upward never
+/// comes back to this contract or to any contract visited before. Only the first
+/// `PARENT_DEPTH_LIMIT` links are checked, and the walk stops at a parent on another chain.
+pub fn check_parent_chain(deps: Deps, env: &Env, parent: &Addr) -> Result<(), ContractError> {
+    let parent_contract = CONTRACT
+        .query(&deps.querier, parent.clone())
+        .map_err(|_| ContractError::InvalidParent(parent.to_string()))?;
+    if parent_contract.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidParent(parent.to_string()));
+    }
+
+    let limit = PARENT_DEPTH_LIMIT
+        .may_load(deps.storage)?
+        .unwrap_or(DEFAULT_PARENT_DEPTH_LIMIT);
+
+    let mut visited = vec![env.contract.address.clone()];
+    let mut current = parent.clone();
+    for _ in 0..limit {
+        if visited.contains(&current) {
+            return Err(ContractError::ParentCycle {
+                addr: current.to_string(),
+            });
+        }
+
+        let resp: ParentResp = deps
+            .querier
+            .query_wasm_smart(current.as_str(), &QueryMsg::Parent {})?;
+        match resp.parent {
+            Some(next) if next.remote.is_some() => break,
+            Some(next) => {
+                visited.push(current);
+                current = Addr::unchecked(next.addr);
+            }
+            None => break,
+        }
+    }
+
+    Ok(())
 }
 
-pub fn migrate(mut deps: DepsMut) -> Result<Response, ContractError> {
+pub fn migrate(mut deps: DepsMut, env: Env) -> Result<Response, ContractError> {
     let contract = get_contract_version(deps.storage)?;
     if contract.contract != CONTRACT_NAME {
         return Err(ContractError::InvalidName(contract.contract));
@@ -54,30 +607,105 @@ pub fn migrate(mut deps: DepsMut) -> Result<Response, ContractError> {
     let resp = match contract.version.as_str() {
         "0.1.0" => migrate_0_1_0(deps.branch())?,
         "0.2.0" => migrate_0_2_0(deps.branch())?,
-        CONTRACT_VERSION => return Ok(Response::new()),
+        "0.3.0" => migrate_0_3_0(deps.branch())?,
+        CONTRACT_VERSION => {
+            backfill_voting_weight(deps.branch(), &env)?;
+            return Ok(Response::new());
+        }
         _ => return Err(ContractError::InvalidVersion(contract.version.to_string())),
     };
 
+    let counter = COUNTERS.load(deps.storage, DEFAULT_COUNTER_ID)?.counter;
+    checkpoint_counter(deps.storage, DEFAULT_COUNTER_ID, env.block.height, counter)?;
+
+    // Versions before 0.4.0 did not record what they received
+    let balance = deps.querier.query_all_balances(&env.contract.address)?;
+    MIGRATED_BALANCE.save(deps.storage, &balance)?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(resp)
 }
 
+/// Recomputes the voting weights from the recorded donations to the default counter, so donors
+/// who donated before voting weights were tracked get theirs. Weights already matching are left
+/// alone, which makes running it again a no-op.
+fn backfill_voting_weight(deps: DepsMut, env: &Env) -> StdResult<()> {
+    let denom = COUNTERS
+        .load(deps.storage, DEFAULT_COUNTER_ID)?
+        .minimal_donation
+        .denom;
+
+    let mut weights: Vec<(Addr, Uint128)> = vec![];
+    for donation in DONATIONS.range(deps.storage, None, None, Order::Ascending) {
+        let (_, donation) = donation?;
+        if donation.counter_id.is_some() {
+            continue;
+        }
+        let amount = amount_of(&donation.funds, &denom);
+        match weights
+            .iter_mut()
+            .find(|(donor, _)| *donor == donation.donor)
+        {
+            Some((_, weight)) => *weight += amount,
+            None => weights.push((donation.donor, amount)),
+        }
+    }
+
+    let mut total = Uint128::zero();
+    for (donor, weight) in weights.into_iter().filter(|(_, weight)| !weight.is_zero()) {
+        total += weight;
+        if VOTING_WEIGHT.may_load(deps.storage, &donor)? != Some(weight) {
+            VOTING_WEIGHT.save(deps.storage, &donor, &weight, env.block.height)?;
+        }
+    }
+    if TOTAL_WEIGHT.may_load(deps.storage)? != Some(total) {
+        TOTAL_WEIGHT.save(deps.storage, &total, env.block.height)?;
+    }
+
+    Ok(())
+}
+
+/// Moves the pre 0.4.0 `owner` and `parent_donation` items, together with the given counter
+/// state, under the default counter id.
+fn migrate_default_counter(
+    deps: DepsMut,
+    counter: u64,
+    minimal_donation: Coin,
+    donating_parent: Option<u64>,
+) -> StdResult<()> {
+    const OWNER: Item<Addr> = Item::new("owner");
+    const PARENT_DONATION: Item<ParentDonation> = Item::new("parent_donation");
+
+    let owner = OWNER.load(deps.storage)?;
+    let parent = PARENT_DONATION.may_load(deps.storage)?;
+    OWNER.remove(deps.storage);
+    PARENT_DONATION.remove(deps.storage);
+
+    COUNTERS.save(
+        deps.storage,
+        DEFAULT_COUNTER_ID,
+        &CounterState {
+            counter,
+            minimal_donation,
+            owner,
+            parent,
+            donating_parent,
+            balance: vec![],
+        },
+    )
+}
+
 pub fn migrate_0_1_0(deps: DepsMut) -> StdResult<Response> {
     const COUNTER: Item<u64> = Item::new("counter");
     const MINIMAL_DONATION: Item<Coin> = Item::new("minimal_donation");
 
     let counter = COUNTER.load(deps.storage)?;
     let minimal_donation = MINIMAL_DONATION.load(deps.storage)?;
+    COUNTER.remove(deps.storage);
+    MINIMAL_DONATION.remove(deps.storage);
 
-    STATE.save(
-        deps.storage,
-        &State {
-            counter,
-            minimal_donation,
-            donating_parent: None,
-        },
-    )?;
+    migrate_default_counter(deps, counter, minimal_donation, None)?;
 
     Ok(Response::new())
 }
@@ -95,130 +723,2630 @@ pub fn migrate_0_2_0(deps: DepsMut) -> StdResult<Response> {
         counter,
         minimal_donation,
     } = OLD_STATE.load(deps.storage)?;
+    OLD_STATE.remove(deps.storage);
 
-    STATE.save(
-        deps.storage,
-        &State {
-            counter,
-            minimal_donation,
-            donating_parent: None,
-        },
-    )?;
+    migrate_default_counter(deps, counter, minimal_donation, None)?;
+
+    Ok(Response::new())
+}
+
+pub fn migrate_0_3_0(deps: DepsMut) -> StdResult<Response> {
+    #[derive(Serialize, Deserialize)]
+    struct OldState {
+        counter: u64,
+        minimal_donation: Coin,
+        donating_parent: Option<u64>,
+    }
+
+    const OLD_STATE: Item<OldState> = Item::new("state");
+
+    let OldState {
+        counter,
+        minimal_donation,
+        donating_parent,
+    } = OLD_STATE.load(deps.storage)?;
+    OLD_STATE.remove(deps.storage);
+
+    migrate_default_counter(deps, counter, minimal_donation, donating_parent)?;
 
     Ok(Response::new())
 }
 
 pub mod query {
-    use crate::{msg::ValueResp, state::STATE};
-    use cosmwasm_std::{Deps, StdResult};
+    use crate::{
+        msg::{
+            AccessListResp, AccessMode, AccessModeResp, AllAllowancesResp, AllowanceResp,
+            AncestorsResp, BeneficiariesResp, Beneficiary, CheckpointPruning,
+            CheckpointPruningResp, ChildrenResp, ClaimableResp, CounterResp, DonationResp,
+            DonorInfo, DonorResp, FeeConfig, FeeResp, FlushBountyResp, GovernanceResp,
+            IbcCounterpartiesResp, IbcCounterparty, IbcHooksResp, InvariantsResp, ListCountersResp,
+            ListDonorsResp, ListSubscriptionsResp, MultisigInfo, MultisigResp, NextDueResp,
+            OwnerActionResp, Parent, ParentForwards, ParentResp, ParentStatsResp, ProposalListResp,
+            ProposalResp, QueryMsg, ReceiptConfig, ReceiptResp, SubscriptionResp, TotalOwedResp,
+            TreeEdge, ValueResp, VoteInfo, VoteListResp, VoteResp, VotingWeightResp,
+            WithdrawableResp,
+        },
+        state::{
+            Ballot, ParentDonation, Proposal, Subscription, ACCESS_LIST, ACCESS_MODE, ALLOWANCES,
+            BALLOTS, BOUNTIES_PAID, CHECKPOINT_PRUNING, CHILDREN, CLAIMED, COUNTERS,
+            COUNTER_CHECKPOINTS, DEFAULT_COUNTER_ID, DEFAULT_PARENT_DEPTH_LIMIT, DONATIONS, DONORS,
+            ESCROWED, FEE, FEES_COLLECTED, FEE_ADMIN, FLUSH_BOUNTY, FORWARDED, GOVERNANCE,
+            IBC_COUNTERPARTIES, IBC_HOOKS, MIGRATED_BALANCE, MULTISIG, NAMED_RECEIVED,
+            NAMED_WITHDRAWN, OWED, OWNER_ACTIONS, PAYOUT, PENDING_FORWARDS, PENDING_TRANSFERS,
+            PROPOSALS, PRUNED_CHECKPOINTS, RECEIPT, RECEIVED, SUBSCRIPTIONS, SUBSCRIPTION_QUEUE,
+            TOTAL_OWED, TOTAL_WEIGHT, VOTING_WEIGHT, WITHDRAWN,
+        },
+    };
+    use cosmwasm_std::{Addr, BlockInfo, Coin, Deps, Env, Order, StdError, StdResult, Timestamp};
+    use cw_storage_plus::Bound;
+    use cw_utils::Expiration;
 
     pub fn value(deps: Deps) -> StdResult<ValueResp> {
-        let value = STATE.load(deps.storage)?.counter;
+        let value = COUNTERS.load(deps.storage, DEFAULT_COUNTER_ID)?.counter;
         Ok(ValueResp { value })
     }
-}
 
-pub mod exec {
-    use cosmwasm_std::{BankMsg, DepsMut, Env, MessageInfo, Response, StdResult, WasmMsg, to_binary};
+    pub fn value_at_height(
+        deps: Deps,
+        height: u64,
+        counter_id: Option<String>,
+    ) -> StdResult<ValueResp> {
+        let id = counter_id.as_deref().unwrap_or(DEFAULT_COUNTER_ID);
+        let not_found =
+            || StdError::not_found(format!("counter {} checkpoint at height {}", id, height));
 
-    use crate::{
-        error::ContractError,
-        state::{OWNER, STATE, PARENT_DONATION}, msg::ExecMsg,
-    };
+        let pruned = PRUNED_CHECKPOINTS.may_load(deps.storage, id)?;
+        if pruned.is_some_and(|pruned| height < pruned) {
+            return Err(not_found());
+        }
 
-    pub fn donate(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
-        let mut state = STATE.load(deps.storage)?;
-        let mut resp = Response::new();
+        // Snapshots hold values at the start of a block, which is the end of the one before
+        let value = COUNTER_CHECKPOINTS
+            .may_load_at_height(deps.storage, id, height + 1)?
+            .ok_or_else(not_found)?;
 
-        if state.minimal_donation.amount.is_zero()
-            || info.funds.iter().any(|coin| {
-                coin.denom == state.minimal_donation.denom
-                    && coin.amount >= state.minimal_donation.amount
-            })
-        {
-            state.counter += 1;
-            if let Some(parent) = &mut state.donating_parent {
-                *parent -= 1;
-    
-                if *parent == 0 {
-                    let parent_donation = PARENT_DONATION.load(deps.storage)?;
-                    *parent = parent_donation.donating_parent_period;
-    
-                    let funds: Vec<_> = deps
-                        .querier
-                        .query_all_balances(env.contract.address)?
-                        .into_iter()
-                        .map(|mut coin| {
-                            coin.amount = coin.amount * parent_donation.part;
-                            coin
-                        })
-                        .collect();
-    
-                    let msg = WasmMsg::Execute {
-                        contract_addr: parent_donation.address.to_string(),
-                        msg: to_binary(&ExecMsg::Donate {})?,
-                        funds,
-                    };
-    
-                    resp = resp
-                        .add_message(msg)
-                        .add_attribute("donated_to_parent", parent_donation.address.to_string());
-                }
-            }
-            
-            STATE.save(deps.storage, &state)?;
+        Ok(ValueResp { value })
+    }
+
+    pub fn checkpoint_pruning(deps: Deps) -> StdResult<CheckpointPruningResp> {
+        let pruning = CHECKPOINT_PRUNING
+            .may_load(deps.storage)?
+            .unwrap_or(CheckpointPruning::Never);
+        Ok(CheckpointPruningResp { pruning })
+    }
+
+    fn to_parent(parent: ParentDonation) -> Parent {
+        Parent {
+            addr: parent.address.into_string(),
+            donating_period: parent.donating_parent_period,
+            part: parent.part,
+            remote: parent.remote,
+            interval: parent.interval,
         }
-        //COUNTER.update(deps.storage, |counter| -> StdResult<_> { Ok(counter + 1) })?;
+    }
 
-        resp = resp
-            .add_attribute("action", "donate")
-            .add_attribute("sender", info.sender.as_str())
-            .add_attribute("counter", state.counter.to_string());
+    pub fn parent(deps: Deps, env: Env) -> StdResult<ParentResp> {
+        let parent = COUNTERS.load(deps.storage, DEFAULT_COUNTER_ID)?.parent;
 
-        Ok(resp)
+        let next_forward = match parent.as_ref().and_then(|parent| parent.interval) {
+            Some(interval) => Some(
+                super::next_forward(deps.storage, DEFAULT_COUNTER_ID, interval)?
+                    .unwrap_or(Expiration::AtHeight(env.block.height)),
+            ),
+            None => None,
+        };
+
+        Ok(ParentResp {
+            parent: parent.map(to_parent),
+            next_forward,
+        })
     }
 
-    pub fn reset(
-        deps: DepsMut,
-        info: MessageInfo,
-        counter: u64,
-    ) -> Result<Response, ContractError> {
-        let owner = OWNER.load(deps.storage)?;
-        if info.sender != owner {
-            return Err(ContractError::Unauthorized {
-                owner: owner.to_string(),
-            });
+    pub fn ancestors(deps: Deps, env: Env, limit: Option<u32>) -> StdResult<AncestorsResp> {
+        let limit = limit.unwrap_or(DEFAULT_PARENT_DEPTH_LIMIT);
+        let parent = match COUNTERS.load(deps.storage, DEFAULT_COUNTER_ID)?.parent {
+            Some(parent) if limit > 0 => parent,
+            _ => return Ok(AncestorsResp { ancestors: vec![] }),
+        };
+
+        // Nothing can be queried across chains, so a remote parent ends the path
+        if parent.remote.is_some() {
+            let ancestors = vec![TreeEdge {
+                addr: parent.address,
+                forwarded: vec![],
+            }];
+            return Ok(AncestorsResp { ancestors });
         }
+        let parent = parent.address;
 
-        STATE.update(deps.storage, |mut state| -> StdResult<_> {
-            state.counter = counter;
-            Ok(state)
-        })?;
+        let forwarded = CHILDREN
+            .query(&deps.querier, parent.clone(), &env.contract.address)?
+            .unwrap_or_default();
 
-        let resp = Response::new()
-            .add_attribute("action", "reset")
-            .add_attribute("sender", info.sender.as_str())
-            .add_attribute("counter", counter.to_string());
-        Ok(resp)
-    }
+        let mut ancestors = vec![TreeEdge {
+            addr: parent.clone(),
+            forwarded,
+        }];
 
-    pub fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
-        let owner = OWNER.load(deps.storage)?;
-        if info.sender != owner {
-            return Err(ContractError::Unauthorized {
-                owner: owner.to_string(),
-            });
+        if limit > 1 {
+            let resp: AncestorsResp = deps.querier.query_wasm_smart(
+                parent.as_str(),
+                &QueryMsg::Ancestors {
+                    limit: Some(limit - 1),
+                },
+            )?;
+            ancestors.extend(resp.ancestors);
         }
 
-        let balance = deps.querier.query_all_balances(&env.contract.address)?;
-        let bank_msg = BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: balance,
-        };
-        let resp = Response::new()
-            .add_message(bank_msg)
+        Ok(AncestorsResp { ancestors })
+    }
+
+    pub fn children(deps: Deps) -> StdResult<ChildrenResp> {
+        let children = CHILDREN
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|child| child.map(|(addr, forwarded)| TreeEdge { addr, forwarded }))
+            .collect::<StdResult<_>>()?;
+
+        Ok(ChildrenResp { children })
+    }
+
+    pub fn parent_stats(deps: Deps) -> StdResult<ParentStatsResp> {
+        let parents = FORWARDED
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|stats| {
+                stats.map(|(addr, stats)| ParentForwards {
+                    addr,
+                    forwarded: stats.forwarded,
+                    count: stats.count,
+                    last_height: stats.last_height,
+                    refunded: stats.refunded,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        let received = RECEIVED.may_load(deps.storage)?.unwrap_or_default();
+
+        Ok(ParentStatsResp {
+            parents,
+            received_from_children: received.from_children,
+            received_from_users: received.from_users,
+        })
+    }
+
+    pub fn donation(deps: Deps, id: u64) -> StdResult<DonationResp> {
+        let donation = DONATIONS.load(deps.storage, id)?;
+        Ok(DonationResp {
+            donor: donation.donor,
+            relayer: donation.relayer,
+            funds: donation.funds,
+            memo: donation.memo,
+            counted: donation.counted,
+            height: donation.height,
+            counter_id: donation.counter_id,
+        })
+    }
+
+    pub fn donor(deps: Deps, addr: String) -> StdResult<DonorResp> {
+        let addr = deps.api.addr_validate(&addr)?;
+        let stats = DONORS.may_load(deps.storage, &addr)?.unwrap_or_default();
+        Ok(DonorResp {
+            donated: stats.donated,
+            donations: stats.donations,
+        })
+    }
+
+    pub fn list_donors(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ListDonorsResp> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let donors = DONORS
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|donor| {
+                donor.map(|(addr, stats)| DonorInfo {
+                    addr,
+                    donated: stats.donated,
+                    donations: stats.donations,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ListDonorsResp { donors })
+    }
+
+    pub fn access_mode(deps: Deps) -> StdResult<AccessModeResp> {
+        let mode = ACCESS_MODE
+            .may_load(deps.storage)?
+            .unwrap_or(AccessMode::Open);
+        Ok(AccessModeResp { mode })
+    }
+
+    pub fn access_list(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AccessListResp> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let addrs = ACCESS_LIST
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+
+        Ok(AccessListResp { addrs })
+    }
+
+    pub fn ibc_counterparties(deps: Deps) -> StdResult<IbcCounterpartiesResp> {
+        let counterparties = IBC_COUNTERPARTIES
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|key| {
+                key.map(|(connection_id, port_id)| IbcCounterparty {
+                    connection_id,
+                    port_id,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(IbcCounterpartiesResp { counterparties })
+    }
+
+    pub fn ibc_hooks(deps: Deps) -> StdResult<IbcHooksResp> {
+        let enabled = IBC_HOOKS.may_load(deps.storage)?.unwrap_or(false);
+        Ok(IbcHooksResp { enabled })
+    }
+
+    pub fn withdrawable(deps: Deps, env: Env) -> StdResult<WithdrawableResp> {
+        let amount = super::withdrawable(deps, &env)?;
+        Ok(WithdrawableResp { amount })
+    }
+
+    pub fn beneficiaries(deps: Deps) -> StdResult<BeneficiariesResp> {
+        let resp = match PAYOUT.may_load(deps.storage)? {
+            Some(payout) => BeneficiariesResp {
+                beneficiaries: payout
+                    .beneficiaries
+                    .into_iter()
+                    .map(|beneficiary| Beneficiary {
+                        addr: beneficiary.addr.into_string(),
+                        share: beneficiary.share,
+                    })
+                    .collect(),
+                remainder: Some(payout.remainder.into_string()),
+                mode: Some(payout.mode),
+            },
+            None => BeneficiariesResp {
+                beneficiaries: vec![],
+                remainder: None,
+                mode: None,
+            },
+        };
+        Ok(resp)
+    }
+
+    pub fn claimable(deps: Deps, addr: String) -> StdResult<ClaimableResp> {
+        let addr = deps.api.addr_validate(&addr)?;
+        let amount = OWED.may_load(deps.storage, &addr)?.unwrap_or_default();
+        Ok(ClaimableResp { amount })
+    }
+
+    pub fn total_owed(deps: Deps) -> StdResult<TotalOwedResp> {
+        let amount = TOTAL_OWED.may_load(deps.storage)?.unwrap_or_default();
+        Ok(TotalOwedResp { amount })
+    }
+
+    pub fn fee(deps: Deps) -> StdResult<FeeResp> {
+        let fee = FEE.may_load(deps.storage)?.map(|fee| FeeConfig {
+            recipient: fee.recipient.into_string(),
+            rate: fee.rate,
+        });
+        let fee_admin = FEE_ADMIN.may_load(deps.storage)?;
+        let collected = FEES_COLLECTED.may_load(deps.storage)?.unwrap_or_default();
+
+        Ok(FeeResp {
+            fee,
+            fee_admin,
+            collected,
+        })
+    }
+
+    pub fn receipt(deps: Deps) -> StdResult<ReceiptResp> {
+        let receipt = RECEIPT
+            .may_load(deps.storage)?
+            .map(|receipt| ReceiptConfig {
+                token: receipt.token.into_string(),
+                redeemable: receipt.redeemable,
+            });
+        Ok(ReceiptResp { receipt })
+    }
+
+    pub fn governance(deps: Deps) -> StdResult<GovernanceResp> {
+        let config = GOVERNANCE.may_load(deps.storage)?;
+        Ok(GovernanceResp { config })
+    }
+
+    pub fn multisig(deps: Deps) -> StdResult<MultisigResp> {
+        let multisig = MULTISIG
+            .may_load(deps.storage)?
+            .map(|multisig| MultisigInfo {
+                members: multisig.members,
+                threshold: multisig.threshold,
+            });
+        Ok(MultisigResp { multisig })
+    }
+
+    pub fn owner_action(deps: Deps, id: u64) -> StdResult<OwnerActionResp> {
+        let owner_action = OWNER_ACTIONS.load(deps.storage, id)?;
+        Ok(OwnerActionResp {
+            id,
+            proposer: owner_action.proposer,
+            action: owner_action.action,
+            approvals: owner_action.approvals,
+            executed: owner_action.executed,
+        })
+    }
+
+    pub fn voting_weight(deps: Deps, addr: String) -> StdResult<VotingWeightResp> {
+        let addr = deps.api.addr_validate(&addr)?;
+        let weight = VOTING_WEIGHT
+            .may_load(deps.storage, &addr)?
+            .unwrap_or_default();
+        let total_weight = TOTAL_WEIGHT.may_load(deps.storage)?.unwrap_or_default();
+        Ok(VotingWeightResp {
+            weight,
+            total_weight,
+        })
+    }
+
+    fn to_proposal(block: &BlockInfo, id: u64, proposal: Proposal) -> ProposalResp {
+        ProposalResp {
+            id,
+            status: super::proposal_status(&proposal, block),
+            proposer: proposal.proposer,
+            recipient: proposal.recipient,
+            amount: proposal.amount,
+            description: proposal.description,
+            expires: proposal.expires,
+            yes: proposal.yes,
+            no: proposal.no,
+            total_weight: proposal.total_weight,
+        }
+    }
+
+    pub fn proposal(deps: Deps, env: Env, id: u64) -> StdResult<ProposalResp> {
+        let proposal = PROPOSALS.load(deps.storage, id)?;
+        Ok(to_proposal(&env.block, id, proposal))
+    }
+
+    pub fn list_proposals(
+        deps: Deps,
+        env: Env,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<ProposalListResp> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+
+        let proposals = PROPOSALS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|proposal| proposal.map(|(id, proposal)| to_proposal(&env.block, id, proposal)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(ProposalListResp { proposals })
+    }
+
+    fn to_vote(voter: Addr, ballot: Ballot) -> VoteInfo {
+        VoteInfo {
+            voter,
+            yes: ballot.yes,
+            weight: ballot.weight,
+        }
+    }
+
+    pub fn vote(deps: Deps, id: u64, voter: String) -> StdResult<VoteResp> {
+        let voter = deps.api.addr_validate(&voter)?;
+        let vote = BALLOTS
+            .may_load(deps.storage, (id, &voter))?
+            .map(|ballot| to_vote(voter, ballot));
+        Ok(VoteResp { vote })
+    }
+
+    pub fn list_votes(
+        deps: Deps,
+        id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<VoteListResp> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let votes = BALLOTS
+            .prefix(id)
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|ballot| ballot.map(|(voter, ballot)| to_vote(voter, ballot)))
+            .collect::<StdResult<_>>()?;
+
+        Ok(VoteListResp { votes })
+    }
+
+    fn to_subscription(id: u64, subscription: Subscription) -> SubscriptionResp {
+        SubscriptionResp {
+            id,
+            donor: subscription.donor,
+            amount: subscription.amount,
+            period: subscription.period,
+            remaining: subscription.remaining,
+            next_due: subscription.next_due,
+        }
+    }
+
+    pub fn subscription(deps: Deps, id: u64) -> StdResult<SubscriptionResp> {
+        let subscription = SUBSCRIPTIONS.load(deps.storage, id)?;
+        Ok(to_subscription(id, subscription))
+    }
+
+    pub fn list_subscriptions(
+        deps: Deps,
+        donor: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<ListSubscriptionsResp> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+        let donor = donor
+            .map(|donor| deps.api.addr_validate(&donor))
+            .transpose()?;
+
+        let subscriptions = SUBSCRIPTIONS
+            .range(
+                deps.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .filter(|subscription| match (subscription, &donor) {
+                (Ok((_, subscription)), Some(donor)) => subscription.donor == *donor,
+                _ => true,
+            })
+            .take(limit)
+            .map(|subscription| {
+                subscription.map(|(id, subscription)| to_subscription(id, subscription))
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ListSubscriptionsResp { subscriptions })
+    }
+
+    /// Sorted by denom without empty amounts, so totals can be compared.
+    fn normalized(coins: &[Coin]) -> Vec<Coin> {
+        let mut normalized = vec![];
+        super::add_coins(&mut normalized, coins);
+        normalized.sort_by(|a, b| a.denom.cmp(&b.denom));
+        normalized
+    }
+
+    fn coins_string(coins: &[Coin]) -> String {
+        if coins.is_empty() {
+            return "nothing".to_owned();
+        }
+        coins
+            .iter()
+            .map(Coin::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn invariants(deps: Deps, env: Env) -> StdResult<InvariantsResp> {
+        let mut violations = vec![];
+
+        let received = RECEIVED.may_load(deps.storage)?.unwrap_or_default();
+        let mut total_received = received.from_users;
+        super::add_coins(&mut total_received, &received.from_children);
+        super::add_coins(
+            &mut total_received,
+            &NAMED_RECEIVED.may_load(deps.storage)?.unwrap_or_default(),
+        );
+        let total_received = normalized(&total_received);
+
+        // Subscription deposits are held without being received yet, and funds held before
+        // migrating were never recorded as received
+        let mut expected = total_received.clone();
+        for held in [&ESCROWED, &MIGRATED_BALANCE] {
+            super::add_coins(
+                &mut expected,
+                &held.may_load(deps.storage)?.unwrap_or_default(),
+            );
+        }
+        let expected = normalized(&expected);
+
+        let mut accounted = deps.querier.query_all_balances(&env.contract.address)?;
+        for stats in FORWARDED.range(deps.storage, None, None, Order::Ascending) {
+            let (_, stats) = stats?;
+            super::add_coins(&mut accounted, &stats.forwarded);
+        }
+        for transfer in PENDING_TRANSFERS.range(deps.storage, None, None, Order::Ascending) {
+            let (_, transfer) = transfer?;
+            super::add_coins(&mut accounted, &[transfer.amount]);
+        }
+        for paid in [
+            &WITHDRAWN,
+            &NAMED_WITHDRAWN,
+            &FEES_COLLECTED,
+            &CLAIMED,
+            &BOUNTIES_PAID,
+        ] {
+            super::add_coins(
+                &mut accounted,
+                &paid.may_load(deps.storage)?.unwrap_or_default(),
+            );
+        }
+        let accounted = normalized(&accounted);
+        if accounted != expected {
+            violations.push(format!(
+                "balance, forwards and withdrawals add up to {} instead of {}",
+                coins_string(&accounted),
+                coins_string(&expected)
+            ));
+        }
+
+        for counter in COUNTERS.range(deps.storage, None, None, Order::Ascending) {
+            let (id, counter) = counter?;
+            if let (Some(countdown), Some(parent)) = (counter.donating_parent, &counter.parent) {
+                if countdown > parent.donating_parent_period {
+                    violations.push(format!(
+                        "counter {} parent countdown {} is above its period {}",
+                        id, countdown, parent.donating_parent_period
+                    ));
+                }
+            }
+        }
+
+        Ok(InvariantsResp {
+            violations,
+            received: total_received,
+        })
+    }
+
+    pub fn flush_bounty(deps: Deps, counter_id: Option<String>) -> StdResult<FlushBountyResp> {
+        let id = counter_id.as_deref().unwrap_or(DEFAULT_COUNTER_ID);
+        let bounty = FLUSH_BOUNTY.may_load(deps.storage)?;
+        let pending = PENDING_FORWARDS.has(deps.storage, id);
+        Ok(FlushBountyResp { bounty, pending })
+    }
+
+    pub fn next_due(deps: Deps) -> StdResult<NextDueResp> {
+        let next_due = SUBSCRIPTION_QUEUE
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .transpose()?
+            .map(|(due, _)| Timestamp::from_nanos(due));
+        Ok(NextDueResp { next_due })
+    }
+
+    pub fn allowance(deps: Deps, spender: String, denom: String) -> StdResult<AllowanceResp> {
+        let spender = deps.api.addr_validate(&spender)?;
+        let allowance = ALLOWANCES
+            .may_load(deps.storage, (&spender, denom.as_str()))?
+            .unwrap_or_default();
+
+        Ok(AllowanceResp {
+            allowance: Coin {
+                denom,
+                amount: allowance.amount,
+            },
+            expires: allowance.expires,
+        })
+    }
+
+    pub fn all_allowances(
+        deps: Deps,
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllAllowancesResp> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+        let spender = deps.api.addr_validate(&spender)?;
+
+        let allowances = ALLOWANCES
+            .prefix(&spender)
+            .range(
+                deps.storage,
+                start_after.as_deref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|allowance| {
+                allowance.map(|(denom, allowance)| AllowanceResp {
+                    allowance: Coin {
+                        denom,
+                        amount: allowance.amount,
+                    },
+                    expires: allowance.expires,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(AllAllowancesResp { allowances })
+    }
+
+    pub fn list_counters(
+        deps: Deps,
+        env: Env,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ListCountersResp> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+
+        let counters = COUNTERS
+            .range(
+                deps.storage,
+                start_after.as_deref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|counter| -> StdResult<_> {
+                let (id, counter) = counter?;
+                let withdrawable = if id == DEFAULT_COUNTER_ID {
+                    super::withdrawable(deps, &env)?
+                } else {
+                    counter.balance
+                };
+
+                Ok(CounterResp {
+                    id,
+                    counter: counter.counter,
+                    minimal_donation: counter.minimal_donation,
+                    owner: counter.owner,
+                    parent: counter.parent.map(to_parent),
+                    withdrawable,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ListCountersResp { counters })
+    }
+}
+
+pub mod exec {
+    #[cfg(feature = "ibc")]
+    use cosmwasm_std::IbcMsg;
+    use cosmwasm_std::{
+        from_json, to_json_binary, Addr, BankMsg, Coin, Decimal, Deps, DepsMut, Empty, Env,
+        MessageInfo, Order, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
+    };
+    use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, TokenInfoResponse};
+    use cw_storage_plus::Bound;
+    use cw_utils::Expiration;
+
+    use crate::{
+        error::ContractError,
+        events,
+        msg::{
+            AccessMode, Beneficiary, CheckpointPruning, ExecMsg, FeeConfig, GovernanceConfig,
+            IbcCounterparty, Owner, Parent, ParentResp, PayoutMode, ProposalStatus, QueryMsg,
+            ReceiptConfig, ReceiveMsg, VestingSchedule,
+        },
+        state::{
+            Ballot, BeneficiaryShare, CounterState, Donation, Multisig, OwnerAction,
+            ParentDonation, Payout, Proposal, Receipt, Subscription, ACCESS_LIST, ACCESS_MODE,
+            ALLOWANCES, BALLOTS, BOUNTIES_PAID, CHECKPOINT_PRUNING, CHILDREN, CLAIMED,
+            COUNTED_DONATIONS, COUNTERS, DEFAULT_COUNTER_ID, DONATIONS, DONATION_COUNT, DONORS,
+            ESCROWED, FEE, FEES_COLLECTED, FEE_ADMIN, FLUSH_BOUNTY, GOVERNANCE, IBC_COUNTERPARTIES,
+            IBC_HOOKS, MULTISIG, NAMED_BALANCE, NAMED_RECEIVED, NAMED_WITHDRAWN, OWED,
+            OWNER_ACTIONS, OWNER_ACTION_COUNT, PAYOUT, PENDING_FORWARDS, PROPOSALS, PROPOSAL_COUNT,
+            RECEIPT, RECEIVED, SUBSCRIPTIONS, SUBSCRIPTION_COUNT, SUBSCRIPTION_QUEUE, TOTAL_OWED,
+            TOTAL_WEIGHT, VESTING, VOTING_WEIGHT,
+        },
+    };
+    #[cfg(feature = "ibc")]
+    use crate::{
+        msg::CounterPacket,
+        state::{IBC_CHANNELS, SYNCED},
+    };
+
+    /// Loads counter `id`, failing unless `sender` owns it.
+    fn ensure_counter_owner(
+        deps: Deps,
+        id: &str,
+        sender: &Addr,
+    ) -> Result<CounterState, ContractError> {
+        let counter = COUNTERS.load(deps.storage, id)?;
+        if *sender != counter.owner {
+            if id == DEFAULT_COUNTER_ID {
+                let multisig = MULTISIG.may_load(deps.storage)?;
+                if matches!(multisig, Some(multisig) if multisig.members.contains(sender)) {
+                    return Err(ContractError::MultisigApprovalRequired {});
+                }
+            }
+            return Err(ContractError::Unauthorized {
+                owner: counter.owner.to_string(),
+            });
+        }
+
+        Ok(counter)
+    }
+
+    fn ensure_owner(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+        ensure_counter_owner(deps, DEFAULT_COUNTER_ID, sender)?;
+        Ok(())
+    }
+
+    /// Whether the access mode lets `sender` donate.
+    fn donor_allowed(deps: Deps, sender: &Addr) -> StdResult<bool> {
+        let listed = ACCESS_LIST.has(deps.storage, sender);
+        let mode = ACCESS_MODE
+            .may_load(deps.storage)?
+            .unwrap_or(AccessMode::Open);
+        let allowed = match mode {
+            AccessMode::Open => true,
+            AccessMode::AllowList => listed,
+            AccessMode::DenyList => !listed,
+        };
+        Ok(allowed)
+    }
+
+    /// Fails once donors decide on withdrawals from the default counter.
+    fn ensure_no_governance(deps: Deps) -> Result<(), ContractError> {
+        if GOVERNANCE.may_load(deps.storage)?.is_some() {
+            return Err(ContractError::GovernanceEnabled {});
+        }
+        Ok(())
+    }
+
+    /// Sends `funds` of counter `counter_id` to its parent, donating to it on this chain or
+    /// with an ICS-20 transfer per coin to a remote one. Remote forwards are only recorded once
+    /// ibc-hooks reports through `sudo` that the transfers succeeded.
+    #[cfg_attr(not(feature = "ibc"), allow(unused_variables))]
+    fn forward_to_parent(
+        deps: DepsMut,
+        env: &Env,
+        counter_id: &str,
+        parent: &ParentDonation,
+        funds: Vec<Coin>,
+    ) -> Result<Vec<SubMsg>, ContractError> {
+        match &parent.remote {
+            None => {
+                super::record_forward(deps, env, &parent.address, &funds)?;
+                let donate = WasmMsg::Execute {
+                    contract_addr: parent.address.to_string(),
+                    msg: to_json_binary(&ExecMsg::Donate {
+                        memo: None,
+                        on_behalf_of: None,
+                        counter_id: None,
+                    })?,
+                    funds,
+                };
+                Ok(vec![SubMsg::new(donate)])
+            }
+            #[cfg(feature = "ibc")]
+            Some(remote) => Ok(crate::transfer::transfer_to_parent(
+                deps, env, counter_id, parent, remote, funds,
+            )?),
+            #[cfg(not(feature = "ibc"))]
+            Some(_) => Err(ContractError::IbcDisabled {}),
+        }
+    }
+
+    pub fn donate(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        memo: Option<String>,
+        on_behalf_of: Option<String>,
+        counter_id: Option<String>,
+    ) -> Result<Response, ContractError> {
+        if let Some(memo) = &memo {
+            super::validate_memo(memo)?;
+        }
+        let donor = match on_behalf_of {
+            Some(donor) => deps.api.addr_validate(&donor)?,
+            None => info.sender.clone(),
+        };
+        let counter_id = counter_id.filter(|id| id != DEFAULT_COUNTER_ID);
+
+        // Children are checked too, anyone can register a contract forwarding their donations
+        if !donor_allowed(deps.as_ref(), &info.sender)? {
+            return Err(ContractError::DonorNotAllowed {
+                addr: info.sender.into_string(),
+            });
+        }
+
+        let child = CHILDREN.may_load(deps.storage, &info.sender)?;
+        let (mut resp, counted, counter) = match &counter_id {
+            Some(id) => donate_named(deps.branch(), &env, &info, id)?,
+            None => donate_default(deps.branch(), &env, &info, child)?,
+        };
+
+        let id = DONATION_COUNT.may_load(deps.storage)?.unwrap_or_default();
+        DONATION_COUNT.save(deps.storage, &(id + 1))?;
+        DONATIONS.save(
+            deps.storage,
+            id,
+            &Donation {
+                donor: donor.clone(),
+                relayer: (donor != info.sender).then(|| info.sender.clone()),
+                funds: info.funds.clone(),
+                memo: memo.clone(),
+                counted,
+                height: env.block.height,
+                counter_id: counter_id.clone(),
+            },
+        )?;
+        DONORS.update(deps.storage, &donor, |stats| -> StdResult<_> {
+            let mut stats = stats.unwrap_or_default();
+            super::add_coins(&mut stats.donated, &info.funds);
+            stats.donations += 1;
+            Ok(stats)
+        })?;
+
+        resp = resp
+            .add_event(events::donate_event(
+                &info.sender,
+                &donor,
+                &info.funds,
+                counted,
+                counter_id.as_deref().unwrap_or(DEFAULT_COUNTER_ID),
+                counter,
+                id,
+            ))
+            .add_attribute("action", "donate")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("counter", counter.to_string())
+            .add_attribute("donation_id", id.to_string());
+
+        if counter_id.is_none() {
+            let denom = COUNTERS
+                .load(deps.storage, DEFAULT_COUNTER_ID)?
+                .minimal_donation
+                .denom;
+            let amount = super::amount_of(&info.funds, &denom);
+            super::add_voting_weight(deps.storage, env.block.height, &donor, amount)?;
+
+            let receipt = RECEIPT
+                .may_load(deps.storage)?
+                .filter(|_| counted && !amount.is_zero());
+            if let Some(receipt) = receipt {
+                let mint = WasmMsg::Execute {
+                    contract_addr: receipt.token.into_string(),
+                    msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+                        recipient: donor.to_string(),
+                        amount,
+                    })?,
+                    funds: vec![],
+                };
+                resp = resp
+                    .add_message(mint)
+                    .add_attribute("receipt_minted", amount.to_string());
+            }
+        }
+
+        if let Some(counter_id) = counter_id {
+            resp = resp.add_attribute("counter_id", counter_id);
+        }
+        if donor != info.sender {
+            resp = resp.add_attribute("donor", donor.as_str());
+        }
+        if let Some(memo) = memo {
+            resp = resp.add_attribute("memo", memo);
+        }
+
+        Ok(resp)
+    }
+
+    /// Counts a donation to the default counter, taking the fee, crediting pull mode
+    /// beneficiaries and forwarding to the parent. Returns whether it was counted and the
+    /// counter value after it.
+    fn donate_default(
+        mut deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        child: Option<Vec<Coin>>,
+    ) -> Result<(Response, bool, u64), ContractError> {
+        let mut state = COUNTERS.load(deps.storage, DEFAULT_COUNTER_ID)?;
+        let mut resp = Response::new();
+
+        let mut received = RECEIVED.may_load(deps.storage)?.unwrap_or_default();
+        match child {
+            Some(mut forwarded) => {
+                super::add_coins(&mut forwarded, &info.funds);
+                CHILDREN.save(deps.storage, &info.sender, &forwarded)?;
+                super::add_coins(&mut received.from_children, &info.funds);
+            }
+            None => super::add_coins(&mut received.from_users, &info.funds),
+        }
+        RECEIVED.save(deps.storage, &received)?;
+
+        let counted = super::is_counted(&state.minimal_donation, &info.funds);
+        if !counted {
+            return Ok((resp, counted, state.counter));
+        }
+
+        state.counter += 1;
+        let counted_donations = COUNTED_DONATIONS
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        COUNTED_DONATIONS.save(deps.storage, &(counted_donations + 1))?;
+
+        let mut fee = vec![];
+        if let Some(fee_config) = FEE.may_load(deps.storage)? {
+            fee = info
+                .funds
+                .iter()
+                .map(|coin| Coin {
+                    denom: coin.denom.clone(),
+                    amount: coin.amount * fee_config.rate,
+                })
+                .filter(|coin| !coin.amount.is_zero())
+                .collect();
+
+            if !fee.is_empty() {
+                let mut collected = FEES_COLLECTED.may_load(deps.storage)?.unwrap_or_default();
+                super::add_coins(&mut collected, &fee);
+                FEES_COLLECTED.save(deps.storage, &collected)?;
+
+                resp = resp.add_message(BankMsg::Send {
+                    to_address: fee_config.recipient.into_string(),
+                    amount: fee.clone(),
+                });
+            }
+        }
+
+        match PAYOUT.may_load(deps.storage)? {
+            Some(payout) if payout.mode == PayoutMode::Pull => {
+                let funds = super::sub_coins(&info.funds, &fee);
+                super::credit_beneficiaries(deps.branch(), &payout, &funds)?;
+                super::check_owed_invariant(deps.as_ref(), env)?;
+            }
+            _ => (),
+        }
+
+        if let (Some(period), Some(parent_donation)) = (&mut state.donating_parent, &state.parent) {
+            let due = super::forward_due(
+                deps.storage,
+                &env.block,
+                DEFAULT_COUNTER_ID,
+                parent_donation,
+                period,
+            )?;
+
+            if due && FLUSH_BOUNTY.may_load(deps.storage)?.is_some() {
+                PENDING_FORWARDS.save(deps.storage, DEFAULT_COUNTER_ID, &Empty {})?;
+                resp = resp.add_attribute("parent_forward_pending", "true");
+            } else if due {
+                super::start_forward_interval(deps.storage, DEFAULT_COUNTER_ID, &env.block)?;
+
+                let funds = parent_share(deps.as_ref(), env, parent_donation, &fee)?;
+                let event = events::parent_forward_event(&parent_donation.address, &funds);
+                let msgs = forward_to_parent(
+                    deps.branch(),
+                    env,
+                    DEFAULT_COUNTER_ID,
+                    parent_donation,
+                    funds,
+                )?;
+                resp = resp
+                    .add_submessages(msgs)
+                    .add_event(event)
+                    .add_attribute("donated_to_parent", parent_donation.address.to_string());
+            }
+        }
+
+        COUNTERS.save(deps.storage, DEFAULT_COUNTER_ID, &state)?;
+        super::checkpoint_counter(
+            deps.storage,
+            DEFAULT_COUNTER_ID,
+            env.block.height,
+            state.counter,
+        )?;
+
+        Ok((resp, counted, state.counter))
+    }
+
+    /// Part of the named counter `state` funds due to its parent.
+    fn named_parent_share(state: &CounterState, parent_donation: &ParentDonation) -> Vec<Coin> {
+        state
+            .balance
+            .iter()
+            .map(|coin| Coin {
+                denom: coin.denom.clone(),
+                amount: coin.amount * parent_donation.part,
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect()
+    }
+
+    /// Part of the default counter funds due to its parent, leaving out what is owed to
+    /// beneficiaries and `reserved`.
+    fn parent_share(
+        deps: Deps,
+        env: &Env,
+        parent_donation: &ParentDonation,
+        reserved: &[Coin],
+    ) -> StdResult<Vec<Coin>> {
+        let owed = TOTAL_OWED.may_load(deps.storage)?.unwrap_or_default();
+        let funds = super::default_balance(deps, env)?
+            .into_iter()
+            .map(|mut coin| {
+                let reserved =
+                    super::amount_of(&owed, &coin.denom) + super::amount_of(reserved, &coin.denom);
+                coin.amount = coin.amount.saturating_sub(reserved) * parent_donation.part;
+                coin
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+
+        Ok(funds)
+    }
+
+    /// Counts a donation to named counter `id`. All funds go to the counter balance, of which
+    /// its parent periodically gets its part.
+    fn donate_named(
+        mut deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        id: &str,
+    ) -> Result<(Response, bool, u64), ContractError> {
+        let mut state = COUNTERS.load(deps.storage, id)?;
+        let mut named = NAMED_BALANCE.may_load(deps.storage)?.unwrap_or_default();
+        let mut resp = Response::new();
+
+        super::add_coins(&mut state.balance, &info.funds);
+        super::add_coins(&mut named, &info.funds);
+
+        let mut received = NAMED_RECEIVED.may_load(deps.storage)?.unwrap_or_default();
+        super::add_coins(&mut received, &info.funds);
+        NAMED_RECEIVED.save(deps.storage, &received)?;
+
+        let counted = super::is_counted(&state.minimal_donation, &info.funds);
+        if counted {
+            state.counter += 1;
+
+            if let (Some(period), Some(parent_donation)) =
+                (&mut state.donating_parent, &state.parent)
+            {
+                let due =
+                    super::forward_due(deps.storage, &env.block, id, parent_donation, period)?;
+
+                if due && FLUSH_BOUNTY.may_load(deps.storage)?.is_some() {
+                    PENDING_FORWARDS.save(deps.storage, id, &Empty {})?;
+                    resp = resp.add_attribute("parent_forward_pending", "true");
+                } else if due {
+                    super::start_forward_interval(deps.storage, id, &env.block)?;
+
+                    let funds = named_parent_share(&state, parent_donation);
+                    state.balance = super::sub_coins(&state.balance, &funds);
+                    named = super::sub_coins(&named, &funds);
+
+                    let event = events::parent_forward_event(&parent_donation.address, &funds);
+                    let msgs = forward_to_parent(deps.branch(), env, id, parent_donation, funds)?;
+                    resp = resp
+                        .add_submessages(msgs)
+                        .add_event(event)
+                        .add_attribute("donated_to_parent", parent_donation.address.to_string());
+                }
+            }
+        }
+
+        NAMED_BALANCE.save(deps.storage, &named)?;
+        COUNTERS.save(deps.storage, id, &state)?;
+        super::checkpoint_counter(deps.storage, id, env.block.height, state.counter)?;
+
+        Ok((resp, counted, state.counter))
+    }
+
+    pub fn reset(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        counter: u64,
+        counter_id: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let id = counter_id.as_deref().unwrap_or(DEFAULT_COUNTER_ID);
+        let mut state = ensure_counter_owner(deps.as_ref(), id, &info.sender)?;
+
+        state.counter = counter;
+        COUNTERS.save(deps.storage, id, &state)?;
+        super::checkpoint_counter(deps.storage, id, env.block.height, counter)?;
+
+        let mut resp = Response::new()
+            .add_event(events::reset_event(&info.sender, id, counter))
+            .add_attribute("action", "reset")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("counter", counter.to_string());
+        if id != DEFAULT_COUNTER_ID {
+            resp = resp.add_attribute("counter_id", id);
+        }
+        Ok(resp)
+    }
+
+    pub fn withdraw(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Option<Vec<Coin>>,
+        counter_id: Option<String>,
+        recipient: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let recipient = match recipient {
+            Some(recipient) => deps.api.addr_validate(&recipient)?,
+            None => info.sender.clone(),
+        };
+        if recipient == env.contract.address {
+            return Err(ContractError::WithdrawToSelf {});
+        }
+
+        match counter_id.as_deref() {
+            Some(id) if id != DEFAULT_COUNTER_ID => {
+                return withdraw_named(deps, info, amount, id, recipient);
+            }
+            _ => {
+                ensure_owner(deps.as_ref(), &info.sender)?;
+                ensure_no_governance(deps.as_ref())?;
+            }
+        }
+
+        let amount = match amount {
+            Some(amount) => super::merge_coins(&amount),
+            None => super::withdrawable(deps.as_ref(), &env)?,
+        };
+        super::take_withdrawable(deps.branch(), &env, &amount)?;
+
+        let mut resp = Response::new()
+            .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender.as_str());
+
+        let payout = match PAYOUT.may_load(deps.storage)? {
+            Some(payout) if payout.mode == PayoutMode::Push => payout,
+            _ => {
+                if !amount.is_empty() {
+                    resp =
+                        resp.add_event(events::withdraw_event(&info.sender, &recipient, &amount));
+                    let bank_msg = BankMsg::Send {
+                        to_address: recipient.into_string(),
+                        amount,
+                    };
+                    resp = resp.add_message(bank_msg);
+                }
+                return Ok(resp);
+            }
+        };
+
+        let mut remainder = amount.clone();
+        for beneficiary in payout.beneficiaries {
+            let share: Vec<_> = amount
+                .iter()
+                .map(|coin| Coin {
+                    denom: coin.denom.clone(),
+                    amount: coin.amount * beneficiary.share,
+                })
+                .filter(|coin| !coin.amount.is_zero())
+                .collect();
+            if share.is_empty() {
+                continue;
+            }
+
+            remainder = super::sub_coins(&remainder, &share);
+            resp = resp
+                .add_event(events::withdraw_event(
+                    &info.sender,
+                    &beneficiary.addr,
+                    &share,
+                ))
+                .add_message(BankMsg::Send {
+                    to_address: beneficiary.addr.into_string(),
+                    amount: share,
+                });
+        }
+
+        if !remainder.is_empty() {
+            resp = resp
+                .add_event(events::withdraw_event(
+                    &info.sender,
+                    &payout.remainder,
+                    &remainder,
+                ))
+                .add_message(BankMsg::Send {
+                    to_address: payout.remainder.into_string(),
+                    amount: remainder,
+                });
+        }
+
+        Ok(resp)
+    }
+
+    fn withdraw_named(
+        deps: DepsMut,
+        info: MessageInfo,
+        amount: Option<Vec<Coin>>,
+        id: &str,
+        recipient: Addr,
+    ) -> Result<Response, ContractError> {
+        let mut state = ensure_counter_owner(deps.as_ref(), id, &info.sender)?;
+
+        let amount = match amount {
+            Some(amount) => super::merge_coins(&amount),
+            None => state.balance.clone(),
+        };
+        for coin in &amount {
+            let available = super::amount_of(&state.balance, &coin.denom);
+            if coin.amount > available {
+                return Err(ContractError::ExceedsBalance {
+                    available: Coin {
+                        denom: coin.denom.clone(),
+                        amount: available,
+                    },
+                });
+            }
+        }
+
+        state.balance = super::sub_coins(&state.balance, &amount);
+        COUNTERS.save(deps.storage, id, &state)?;
+        let named = NAMED_BALANCE.may_load(deps.storage)?.unwrap_or_default();
+        NAMED_BALANCE.save(deps.storage, &super::sub_coins(&named, &amount))?;
+
+        let mut resp = Response::new()
             .add_attribute("action", "withdraw")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("counter_id", id);
+
+        let amount: Vec<_> = amount
+            .into_iter()
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+        if !amount.is_empty() {
+            let mut withdrawn = NAMED_WITHDRAWN.may_load(deps.storage)?.unwrap_or_default();
+            super::add_coins(&mut withdrawn, &amount);
+            NAMED_WITHDRAWN.save(deps.storage, &withdrawn)?;
+            resp = resp
+                .add_event(events::withdraw_event(&info.sender, &recipient, &amount))
+                .add_message(BankMsg::Send {
+                    to_address: recipient.into_string(),
+                    amount,
+                });
+        }
+
+        Ok(resp)
+    }
+
+    pub fn create_counter(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: String,
+        minimal_donation: Coin,
+        parent: Option<Parent>,
+        owner: Option<String>,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+        super::validate_counter_id(&id)?;
+        if COUNTERS.has(deps.storage, &id) {
+            return Err(ContractError::CounterExists { id });
+        }
+
+        let owner = match owner {
+            Some(owner) => deps.api.addr_validate(&owner)?,
+            None => info.sender.clone(),
+        };
+        let parent = parent
+            .map(|parent| super::parent_donation(deps.as_ref(), &env, parent))
+            .transpose()?;
+
+        COUNTERS.save(
+            deps.storage,
+            &id,
+            &CounterState {
+                counter: 0,
+                minimal_donation,
+                owner: owner.clone(),
+                donating_parent: parent.as_ref().map(|p| p.donating_parent_period),
+                parent,
+                balance: vec![],
+            },
+        )?;
+        super::start_forward_interval(deps.storage, &id, &env.block)?;
+        super::checkpoint_counter(deps.storage, &id, env.block.height, 0)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "create_counter")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("counter_id", id)
+            .add_attribute("owner", owner.as_str());
+        Ok(resp)
+    }
+
+    pub fn update_parent(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        parent: Option<Parent>,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+        ensure_no_governance(deps.as_ref())?;
+
+        let mut resp = Response::new()
+            .add_attribute("action", "update_parent")
+            .add_attribute("sender", info.sender.as_str());
+
+        let mut state = COUNTERS.load(deps.storage, DEFAULT_COUNTER_ID)?;
+        let new_parent = parent
+            .map(|parent| super::parent_donation(deps.as_ref(), &env, parent))
+            .transpose()?;
+
+        // Only parents on this chain keep track of their children
+        let old_parent = state
+            .parent
+            .as_ref()
+            .filter(|parent| parent.remote.is_none())
+            .map(|parent| parent.address.clone());
+        let new_local_parent = new_parent
+            .as_ref()
+            .filter(|parent| parent.remote.is_none())
+            .map(|parent| parent.address.clone());
+
+        if old_parent != new_local_parent {
+            if let Some(old_parent) = old_parent {
+                resp = resp.add_message(WasmMsg::Execute {
+                    contract_addr: old_parent.into_string(),
+                    msg: to_json_binary(&ExecMsg::UnregisterChild {})?,
+                    funds: vec![],
+                });
+            }
+            if let Some(new_parent) = &new_local_parent {
+                resp = resp.add_message(WasmMsg::Execute {
+                    contract_addr: new_parent.to_string(),
+                    msg: to_json_binary(&ExecMsg::RegisterChild {})?,
+                    funds: vec![],
+                });
+            }
+        }
+
+        if let Some(parent) = &new_parent {
+            resp = resp.add_attribute("parent", parent.address.as_str());
+        }
+        state.parent = new_parent;
+        state.donating_parent = state
+            .parent
+            .as_ref()
+            .map(|parent| parent.donating_parent_period);
+        COUNTERS.save(deps.storage, DEFAULT_COUNTER_ID, &state)?;
+        super::start_forward_interval(deps.storage, DEFAULT_COUNTER_ID, &env.block)?;
+
+        Ok(resp)
+    }
+
+    pub fn register_child(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let parent = deps
+            .querier
+            .query_wasm_smart::<ParentResp>(info.sender.as_str(), &QueryMsg::Parent {})
+            .ok()
+            .and_then(|resp| resp.parent);
+        if parent.map(|parent| parent.addr) != Some(env.contract.address.to_string()) {
+            return Err(ContractError::NotChild {
+                addr: info.sender.into_string(),
+            });
+        }
+
+        if !CHILDREN.has(deps.storage, &info.sender) {
+            CHILDREN.save(deps.storage, &info.sender, &vec![])?;
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "register_child")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn unregister_child(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        CHILDREN.remove(deps.storage, &info.sender);
+
+        let resp = Response::new()
+            .add_attribute("action", "unregister_child")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+    pub fn set_access_mode(
+        deps: DepsMut,
+        info: MessageInfo,
+        mode: AccessMode,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        ACCESS_MODE.save(deps.storage, &mode)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "set_access_mode")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn update_access_list(
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        for addr in add {
+            let addr = deps.api.addr_validate(&addr)?;
+            ACCESS_LIST.save(deps.storage, &addr, &Empty {})?;
+        }
+        for addr in remove {
+            let addr = deps.api.addr_validate(&addr)?;
+            ACCESS_LIST.remove(deps.storage, &addr);
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "update_access_list")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn update_ibc_counterparties(
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<IbcCounterparty>,
+        remove: Vec<IbcCounterparty>,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        for counterparty in add {
+            let key = (
+                counterparty.connection_id.as_str(),
+                counterparty.port_id.as_str(),
+            );
+            IBC_COUNTERPARTIES.save(deps.storage, key, &Empty {})?;
+        }
+        for counterparty in remove {
+            let key = (
+                counterparty.connection_id.as_str(),
+                counterparty.port_id.as_str(),
+            );
+            IBC_COUNTERPARTIES.remove(deps.storage, key);
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "update_ibc_counterparties")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+    pub fn increase_allowance(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        amount: Coin,
+        expires: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+        ensure_no_governance(deps.as_ref())?;
+
+        let spender = deps.api.addr_validate(&spender)?;
+        ALLOWANCES.update(
+            deps.storage,
+            (&spender, amount.denom.as_str()),
+            |allowance| -> Result<_, ContractError> {
+                let mut allowance = allowance.unwrap_or_default();
+                if let Some(expires) = expires {
+                    if expires.is_expired(&env.block) {
+                        return Err(ContractError::InvalidExpiration {});
+                    }
+                    allowance.expires = expires;
+                }
+                allowance.amount += amount.amount;
+                Ok(allowance)
+            },
+        )?;
+
+        let resp = Response::new()
+            .add_attribute("action", "increase_allowance")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("spender", spender.as_str())
+            .add_attribute("amount", amount.to_string());
+        Ok(resp)
+    }
+
+    pub fn decrease_allowance(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        amount: Coin,
+        expires: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        let spender = deps.api.addr_validate(&spender)?;
+        let key = (&spender, amount.denom.as_str());
+        let mut allowance = ALLOWANCES.may_load(deps.storage, key)?.unwrap_or_default();
+
+        if amount.amount >= allowance.amount {
+            ALLOWANCES.remove(deps.storage, key);
+        } else {
+            if let Some(expires) = expires {
+                if expires.is_expired(&env.block) {
+                    return Err(ContractError::InvalidExpiration {});
+                }
+                allowance.expires = expires;
+            }
+            allowance.amount -= amount.amount;
+            ALLOWANCES.save(deps.storage, key, &allowance)?;
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "decrease_allowance")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("spender", spender.as_str())
+            .add_attribute("amount", amount.to_string());
+        Ok(resp)
+    }
+
+    pub fn withdraw_allowance(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Coin,
+        recipient: Option<String>,
+    ) -> Result<Response, ContractError> {
+        ensure_no_governance(deps.as_ref())?;
+
+        if amount.amount.is_zero() {
+            return Err(ContractError::ZeroWithdrawal {});
+        }
+
+        let recipient = match recipient {
+            Some(recipient) => deps.api.addr_validate(&recipient)?,
+            None => info.sender.clone(),
+        };
+
+        let key = (&info.sender, amount.denom.as_str());
+        let mut allowance = ALLOWANCES.may_load(deps.storage, key)?.unwrap_or_default();
+        if allowance.expires.is_expired(&env.block) {
+            return Err(ContractError::AllowanceExpired {});
+        }
+
+        if amount.amount > allowance.amount {
+            return Err(ContractError::InsufficientAllowance {
+                allowance: Coin {
+                    denom: amount.denom.clone(),
+                    amount: allowance.amount,
+                },
+            });
+        }
+
+        super::take_withdrawable(deps.branch(), &env, std::slice::from_ref(&amount))?;
+
+        allowance.amount -= amount.amount;
+        if allowance.amount.is_zero() {
+            ALLOWANCES.remove(deps.storage, key);
+        } else {
+            ALLOWANCES.save(deps.storage, key, &allowance)?;
+        }
+
+        let bank_msg = BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![amount.clone()],
+        };
+        let resp = Response::new()
+            .add_message(bank_msg)
+            .add_event(events::withdraw_event(
+                &info.sender,
+                &recipient,
+                std::slice::from_ref(&amount),
+            ))
+            .add_attribute("action", "withdraw_allowance")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("recipient", recipient.as_str())
+            .add_attribute("amount", amount.to_string());
+        Ok(resp)
+    }
+    pub fn set_beneficiaries(
+        deps: DepsMut,
+        info: MessageInfo,
+        beneficiaries: Vec<Beneficiary>,
+        remainder: String,
+        mode: PayoutMode,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+        ensure_no_governance(deps.as_ref())?;
+
+        let total: Decimal = beneficiaries
+            .iter()
+            .map(|beneficiary| beneficiary.share)
+            .sum();
+        if total != Decimal::one() {
+            return Err(ContractError::InvalidShares { total });
+        }
+
+        let beneficiaries = beneficiaries
+            .into_iter()
+            .map(|beneficiary| -> StdResult<_> {
+                Ok(BeneficiaryShare {
+                    addr: deps.api.addr_validate(&beneficiary.addr)?,
+                    share: beneficiary.share,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        let remainder = deps.api.addr_validate(&remainder)?;
+
+        PAYOUT.save(
+            deps.storage,
+            &Payout {
+                beneficiaries,
+                remainder,
+                mode,
+            },
+        )?;
+
+        let resp = Response::new()
+            .add_attribute("action", "set_beneficiaries")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn remove_beneficiaries(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        PAYOUT.remove(deps.storage);
+
+        let resp = Response::new()
+            .add_attribute("action", "remove_beneficiaries")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+    pub fn claim(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let owed = OWED
+            .may_load(deps.storage, &info.sender)?
+            .unwrap_or_default();
+        if owed.is_empty() {
+            return Err(ContractError::NothingToClaim {});
+        }
+
+        super::check_owed_invariant(deps.as_ref(), &env)?;
+
+        OWED.remove(deps.storage, &info.sender);
+        let total_owed = TOTAL_OWED.may_load(deps.storage)?.unwrap_or_default();
+        TOTAL_OWED.save(deps.storage, &super::sub_coins(&total_owed, &owed))?;
+        let mut claimed = CLAIMED.may_load(deps.storage)?.unwrap_or_default();
+        super::add_coins(&mut claimed, &owed);
+        CLAIMED.save(deps.storage, &claimed)?;
+
+        let event = events::withdraw_event(&info.sender, &info.sender, &owed);
+        let bank_msg = BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: owed,
+        };
+        let resp = Response::new()
+            .add_message(bank_msg)
+            .add_event(event)
+            .add_attribute("action", "claim")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+    /// The default counter's owner administers the fee while no fee admin is set.
+    fn ensure_fee_admin(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+        match FEE_ADMIN.may_load(deps.storage)? {
+            Some(admin) if admin == *sender => Ok(()),
+            Some(_) => Err(ContractError::NotFeeAdmin {}),
+            None => ensure_owner(deps, sender).map_err(|_| ContractError::NotFeeAdmin {}),
+        }
+    }
+
+    pub fn update_fee(
+        deps: DepsMut,
+        info: MessageInfo,
+        fee: Option<FeeConfig>,
+    ) -> Result<Response, ContractError> {
+        ensure_fee_admin(deps.as_ref(), &info.sender)?;
+
+        match fee {
+            Some(fee) => {
+                let fee = super::validate_fee(deps.as_ref(), fee)?;
+                FEE.save(deps.storage, &fee)?;
+            }
+            None => FEE.remove(deps.storage),
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "update_fee")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn update_vesting(
+        deps: DepsMut,
+        info: MessageInfo,
+        vesting: Option<VestingSchedule>,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        match vesting {
+            Some(vesting) => {
+                super::validate_vesting(&vesting)?;
+                VESTING.save(deps.storage, &vesting)?;
+            }
+            None => VESTING.remove(deps.storage),
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "update_vesting")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn update_fee_admin(
+        deps: DepsMut,
+        info: MessageInfo,
+        admin: Option<String>,
+    ) -> Result<Response, ContractError> {
+        ensure_fee_admin(deps.as_ref(), &info.sender)?;
+
+        match admin {
+            Some(admin) => {
+                let admin = deps.api.addr_validate(&admin)?;
+                FEE_ADMIN.save(deps.storage, &admin)?;
+            }
+            None => FEE_ADMIN.remove(deps.storage),
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "update_fee_admin")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn set_checkpoint_pruning(
+        deps: DepsMut,
+        info: MessageInfo,
+        pruning: CheckpointPruning,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        CHECKPOINT_PRUNING.save(deps.storage, &pruning)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "set_checkpoint_pruning")
             .add_attribute("sender", info.sender.as_str());
         Ok(resp)
     }
+
+    pub fn set_ibc_hooks(
+        deps: DepsMut,
+        info: MessageInfo,
+        enabled: bool,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        if enabled {
+            IBC_HOOKS.save(deps.storage, &true)?;
+        } else {
+            let remote = COUNTERS
+                .range(deps.storage, None, None, Order::Ascending)
+                .find(|counter| {
+                    counter.as_ref().map_or(true, |(_, state)| {
+                        state
+                            .parent
+                            .as_ref()
+                            .is_some_and(|parent| parent.remote.is_some())
+                    })
+                })
+                .transpose()?;
+            if let Some((id, _)) = remote {
+                return Err(ContractError::RemoteParentInUse { id });
+            }
+            IBC_HOOKS.remove(deps.storage);
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "set_ibc_hooks")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("enabled", enabled.to_string());
+        Ok(resp)
+    }
+
+    #[cfg(feature = "ibc")]
+    pub fn sync_to_remote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        channel: String,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        if !IBC_CHANNELS.has(deps.storage, &channel) {
+            return Err(ContractError::UnknownChannel { channel });
+        }
+
+        let counted = COUNTED_DONATIONS
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        let synced = SYNCED.may_load(deps.storage, &channel)?.unwrap_or_default();
+        let by = counted - synced;
+        if by == 0 {
+            return Err(ContractError::NothingToSync {});
+        }
+        SYNCED.save(deps.storage, &channel, &counted)?;
+
+        let packet = IbcMsg::SendPacket {
+            channel_id: channel.clone(),
+            data: to_json_binary(&CounterPacket::Increment { by })?,
+            timeout: env
+                .block
+                .time
+                .plus_seconds(crate::ibc::PACKET_LIFETIME)
+                .into(),
+        };
+
+        let resp = Response::new()
+            .add_message(packet)
+            .add_attribute("action", "sync_to_remote")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("channel", channel)
+            .add_attribute("by", by.to_string());
+        Ok(resp)
+    }
+
+    pub fn update_receipt(
+        deps: DepsMut,
+        info: MessageInfo,
+        receipt: Option<ReceiptConfig>,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        match receipt {
+            Some(receipt) => {
+                let receipt = Receipt {
+                    token: deps.api.addr_validate(&receipt.token)?,
+                    redeemable: receipt.redeemable,
+                };
+                RECEIPT.save(deps.storage, &receipt)?;
+            }
+            None => RECEIPT.remove(deps.storage),
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "update_receipt")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn receive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let receipt = RECEIPT
+            .may_load(deps.storage)?
+            .filter(|receipt| receipt.token == info.sender)
+            .ok_or_else(|| ContractError::UnknownToken {
+                token: info.sender.to_string(),
+            })?;
+
+        match from_json(&msg.msg)? {
+            ReceiveMsg::Redeem {} => redeem(deps, env, receipt, msg.sender, msg.amount),
+        }
+    }
+
+    fn redeem(
+        deps: DepsMut,
+        env: Env,
+        receipt: Receipt,
+        sender: String,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        if !receipt.redeemable {
+            return Err(ContractError::RedeemDisabled {});
+        }
+        // Redeeming would pay out funds the donors decide on
+        ensure_no_governance(deps.as_ref())?;
+        let sender = deps.api.addr_validate(&sender)?;
+
+        // Supply still includes the tokens being redeemed, they are burned below
+        let token_info: TokenInfoResponse = deps
+            .querier
+            .query_wasm_smart(receipt.token.as_str(), &Cw20QueryMsg::TokenInfo {})?;
+        let payout: Vec<_> = super::withdrawable(deps.as_ref(), &env)?
+            .into_iter()
+            .map(|coin| Coin {
+                amount: coin.amount.multiply_ratio(amount, token_info.total_supply),
+                denom: coin.denom,
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+        super::take_withdrawable(deps, &env, &payout)?;
+
+        let burn = WasmMsg::Execute {
+            contract_addr: receipt.token.into_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        };
+        let mut resp = Response::new()
+            .add_message(burn)
+            .add_attribute("action", "redeem")
+            .add_attribute("sender", sender.as_str())
+            .add_attribute("burned", amount.to_string());
+
+        if !payout.is_empty() {
+            resp = resp
+                .add_event(events::withdraw_event(&sender, &sender, &payout))
+                .add_message(BankMsg::Send {
+                    to_address: sender.into_string(),
+                    amount: payout,
+                });
+        }
+
+        Ok(resp)
+    }
+
+    pub fn enable_governance(
+        deps: DepsMut,
+        info: MessageInfo,
+        config: GovernanceConfig,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+        ensure_no_governance(deps.as_ref())?;
+
+        let valid = |part: Decimal| !part.is_zero() && part <= Decimal::one();
+        if !valid(config.quorum) || !valid(config.threshold) {
+            return Err(ContractError::InvalidGovernanceConfig {});
+        }
+        GOVERNANCE.save(deps.storage, &config)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "enable_governance")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn propose(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: String,
+        amount: Vec<Coin>,
+        description: String,
+    ) -> Result<Response, ContractError> {
+        let config = GOVERNANCE
+            .may_load(deps.storage)?
+            .ok_or(ContractError::GovernanceDisabled {})?;
+
+        let recipient = deps.api.addr_validate(&recipient)?;
+        let amount = super::merge_coins(&amount);
+        if amount.is_empty() {
+            return Err(ContractError::EmptyProposal {});
+        }
+
+        let height = env.block.height;
+        let weight = VOTING_WEIGHT
+            .may_load_at_height(deps.storage, &info.sender, height)?
+            .unwrap_or_default();
+        if weight.is_zero() {
+            return Err(ContractError::NoVotingWeight {});
+        }
+        let total_weight = TOTAL_WEIGHT
+            .may_load_at_height(deps.storage, height)?
+            .unwrap_or_default();
+
+        let id = PROPOSAL_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+        PROPOSAL_COUNT.save(deps.storage, &id)?;
+        PROPOSALS.save(
+            deps.storage,
+            id,
+            &Proposal {
+                proposer: info.sender.clone(),
+                recipient,
+                amount,
+                description,
+                start_height: height,
+                expires: config.voting_period.after(&env.block),
+                status: ProposalStatus::Open,
+                quorum: config.quorum,
+                threshold: config.threshold,
+                total_weight,
+                yes: weight,
+                no: Uint128::zero(),
+            },
+        )?;
+        BALLOTS.save(
+            deps.storage,
+            (id, &info.sender),
+            &Ballot { yes: true, weight },
+        )?;
+
+        let resp = Response::new()
+            .add_attribute("action", "propose")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("proposal_id", id.to_string());
+        Ok(resp)
+    }
+
+    pub fn vote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+        yes: bool,
+    ) -> Result<Response, ContractError> {
+        let mut proposal = PROPOSALS.load(deps.storage, id)?;
+        if super::proposal_status(&proposal, &env.block) != ProposalStatus::Open {
+            return Err(ContractError::ProposalNotOpen { id });
+        }
+        if BALLOTS.has(deps.storage, (id, &info.sender)) {
+            return Err(ContractError::AlreadyVoted { id });
+        }
+
+        let weight = VOTING_WEIGHT
+            .may_load_at_height(deps.storage, &info.sender, proposal.start_height)?
+            .unwrap_or_default();
+        if weight.is_zero() {
+            return Err(ContractError::NoVotingWeight {});
+        }
+
+        if yes {
+            proposal.yes += weight;
+        } else {
+            proposal.no += weight;
+        }
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+        BALLOTS.save(deps.storage, (id, &info.sender), &Ballot { yes, weight })?;
+
+        let status = super::proposal_status(&proposal, &env.block);
+        let resp = Response::new()
+            .add_attribute("action", "vote")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("proposal_id", id.to_string())
+            .add_attribute("yes", yes.to_string())
+            .add_attribute("status", format!("{:?}", status));
+        Ok(resp)
+    }
+
+    pub fn execute_proposal(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let mut proposal = PROPOSALS.load(deps.storage, id)?;
+        if super::proposal_status(&proposal, &env.block) != ProposalStatus::Passed {
+            return Err(ContractError::ProposalNotPassed { id });
+        }
+
+        super::take_withdrawable(deps.branch(), &env, &proposal.amount)?;
+        proposal.status = ProposalStatus::Executed;
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+
+        let resp = Response::new()
+            .add_event(events::withdraw_event(
+                &info.sender,
+                &proposal.recipient,
+                &proposal.amount,
+            ))
+            .add_message(BankMsg::Send {
+                to_address: proposal.recipient.to_string(),
+                amount: proposal.amount,
+            })
+            .add_attribute("action", "execute")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("proposal_id", id.to_string());
+        Ok(resp)
+    }
+
+    pub fn update_owner(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        owner: Owner,
+    ) -> Result<Response, ContractError> {
+        let mut state = ensure_counter_owner(deps.as_ref(), DEFAULT_COUNTER_ID, &info.sender)?;
+
+        state.owner = match owner {
+            Owner::Single { addr } => {
+                let owner = deps.api.addr_validate(&addr)?;
+                MULTISIG.remove(deps.storage);
+                owner
+            }
+            Owner::Multisig(config) => {
+                let members = config
+                    .members
+                    .iter()
+                    .map(|member| deps.api.addr_validate(member))
+                    .collect::<StdResult<Vec<_>>>()?;
+
+                let mut unique = members.clone();
+                unique.sort();
+                unique.dedup();
+                if unique.len() != members.len()
+                    || config.threshold == 0
+                    || config.threshold as usize > members.len()
+                {
+                    return Err(ContractError::InvalidMultisig {});
+                }
+
+                MULTISIG.save(
+                    deps.storage,
+                    &Multisig {
+                        members,
+                        threshold: config.threshold,
+                    },
+                )?;
+                env.contract.address
+            }
+        };
+        COUNTERS.save(deps.storage, DEFAULT_COUNTER_ID, &state)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "update_owner")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("owner", state.owner.as_str());
+        Ok(resp)
+    }
+
+    fn ensure_member(deps: Deps, sender: &Addr) -> Result<Multisig, ContractError> {
+        let multisig = MULTISIG
+            .may_load(deps.storage)?
+            .ok_or(ContractError::MultisigDisabled {})?;
+        if !multisig.members.contains(sender) {
+            return Err(ContractError::NotMultisigMember {});
+        }
+
+        Ok(multisig)
+    }
+
+    /// Fails unless `action` is one only the owner may send. Withdrawals need a recipient, as
+    /// the contract itself runs them.
+    fn ensure_owner_action(action: &ExecMsg) -> Result<(), ContractError> {
+        use ExecMsg::*;
+
+        match action {
+            Withdraw {
+                recipient: None, ..
+            } => Err(ContractError::WithdrawToSelf {}),
+            Reset { .. }
+            | Withdraw { .. }
+            | CreateCounter { .. }
+            | UpdateParent { .. }
+            | SetAccessMode { .. }
+            | UpdateAccessList { .. }
+            | UpdateIbcCounterparties { .. }
+            | IncreaseAllowance { .. }
+            | DecreaseAllowance { .. }
+            | SetBeneficiaries { .. }
+            | RemoveBeneficiaries {}
+            | SetCheckpointPruning { .. }
+            | SyncToRemote { .. }
+            | UpdateReceipt { .. }
+            | EnableGovernance { .. }
+            | UpdateOwner { .. }
+            | UpdateFlushBounty { .. }
+            | UpdateVesting { .. }
+            | SetIbcHooks { .. } => Ok(()),
+            _ => Err(ContractError::InvalidOwnerAction {}),
+        }
+    }
+
+    /// Marks `owner_action` executed and returns the message running it once enough current
+    /// members approved it. The contract sends it to itself, as it is the owner.
+    fn run_if_approved(
+        env: &Env,
+        multisig: &Multisig,
+        owner_action: &mut OwnerAction,
+    ) -> StdResult<Option<WasmMsg>> {
+        let approvals = owner_action
+            .approvals
+            .iter()
+            .filter(|member| multisig.members.contains(member))
+            .count();
+        if approvals < multisig.threshold as usize {
+            return Ok(None);
+        }
+
+        owner_action.executed = true;
+        let msg = WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_json_binary(&owner_action.action)?,
+            funds: vec![],
+        };
+        Ok(Some(msg))
+    }
+
+    pub fn propose_owner_action(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        action: ExecMsg,
+    ) -> Result<Response, ContractError> {
+        let multisig = ensure_member(deps.as_ref(), &info.sender)?;
+        ensure_owner_action(&action)?;
+
+        let id = OWNER_ACTION_COUNT
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            + 1;
+        OWNER_ACTION_COUNT.save(deps.storage, &id)?;
+
+        let mut owner_action = OwnerAction {
+            proposer: info.sender.clone(),
+            action,
+            approvals: vec![info.sender.clone()],
+            executed: false,
+        };
+        let msg = run_if_approved(&env, &multisig, &mut owner_action)?;
+        OWNER_ACTIONS.save(deps.storage, id, &owner_action)?;
+
+        let resp = Response::new()
+            .add_messages(msg)
+            .add_attribute("action", "propose_owner_action")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("owner_action_id", id.to_string())
+            .add_attribute("executed", owner_action.executed.to_string());
+        Ok(resp)
+    }
+
+    pub fn approve_owner_action(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let multisig = ensure_member(deps.as_ref(), &info.sender)?;
+
+        let mut owner_action = OWNER_ACTIONS.load(deps.storage, id)?;
+        if owner_action.executed {
+            return Err(ContractError::OwnerActionExecuted { id });
+        }
+        if owner_action.approvals.contains(&info.sender) {
+            return Err(ContractError::AlreadyApproved { id });
+        }
+
+        owner_action.approvals.push(info.sender.clone());
+        let msg = run_if_approved(&env, &multisig, &mut owner_action)?;
+        OWNER_ACTIONS.save(deps.storage, id, &owner_action)?;
+
+        let resp = Response::new()
+            .add_messages(msg)
+            .add_attribute("action", "approve_owner_action")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("owner_action_id", id.to_string())
+            .add_attribute("executed", owner_action.executed.to_string());
+        Ok(resp)
+    }
+
+    pub fn subscribe(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Coin,
+        period: u64,
+        periods: u32,
+    ) -> Result<Response, ContractError> {
+        if amount.amount.is_zero() || period == 0 || periods == 0 {
+            return Err(ContractError::InvalidSubscription {});
+        }
+        if !donor_allowed(deps.as_ref(), &info.sender)? {
+            return Err(ContractError::DonorNotAllowed {
+                addr: info.sender.into_string(),
+            });
+        }
+
+        let expected = Coin {
+            denom: amount.denom.clone(),
+            amount: amount
+                .amount
+                .checked_mul(Uint128::from(periods))
+                .map_err(StdError::from)?,
+        };
+        if info.funds != vec![expected.clone()] {
+            return Err(ContractError::InvalidSubscriptionDeposit { expected });
+        }
+
+        let mut escrowed = ESCROWED.may_load(deps.storage)?.unwrap_or_default();
+        super::add_coins(&mut escrowed, &info.funds);
+        ESCROWED.save(deps.storage, &escrowed)?;
+
+        let id = SUBSCRIPTION_COUNT
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            + 1;
+        SUBSCRIPTION_COUNT.save(deps.storage, &id)?;
+
+        let next_due = env.block.time;
+        SUBSCRIPTIONS.save(
+            deps.storage,
+            id,
+            &Subscription {
+                donor: info.sender.clone(),
+                amount,
+                period,
+                remaining: periods,
+                next_due,
+            },
+        )?;
+        SUBSCRIPTION_QUEUE.save(deps.storage, (next_due.nanos(), id), &Empty {})?;
+
+        let resp = Response::new()
+            .add_attribute("action", "subscribe")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("subscription_id", id.to_string());
+        Ok(resp)
+    }
+
+    /// Removes subscription `id`, returning the deposit left in it.
+    fn end_subscription(
+        storage: &mut dyn Storage,
+        id: u64,
+        subscription: &Subscription,
+    ) -> StdResult<Coin> {
+        SUBSCRIPTIONS.remove(storage, id);
+        SUBSCRIPTION_QUEUE.remove(storage, (subscription.next_due.nanos(), id));
+
+        let left = Coin {
+            denom: subscription.amount.denom.clone(),
+            amount: subscription.amount.amount * Uint128::from(subscription.remaining),
+        };
+        let escrowed = ESCROWED.may_load(storage)?.unwrap_or_default();
+        ESCROWED.save(
+            storage,
+            &super::sub_coins(&escrowed, std::slice::from_ref(&left)),
+        )?;
+
+        Ok(left)
+    }
+
+    pub fn cancel_subscription(
+        deps: DepsMut,
+        info: MessageInfo,
+        id: u64,
+    ) -> Result<Response, ContractError> {
+        let subscription = SUBSCRIPTIONS.load(deps.storage, id)?;
+        if subscription.donor != info.sender {
+            return Err(ContractError::NotSubscriber { id });
+        }
+
+        let refund = end_subscription(deps.storage, id, &subscription)?;
+
+        let resp = Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![refund.clone()],
+            })
+            .add_attribute("action", "cancel_subscription")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("subscription_id", id.to_string())
+            .add_attribute("refund", refund.to_string());
+        Ok(resp)
+    }
+
+    /// Donates due subscription payments in the order they became due, each as a donation of
+    /// its donor to the default counter. Subscriptions of donors no longer allowed to donate
+    /// are ended and refunded instead.
+    pub fn process_subscriptions(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        limit: Option<u32>,
+    ) -> Result<Response, ContractError> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT);
+        let due = Bound::inclusive((env.block.time.nanos(), u64::MAX));
+
+        let mut resp = Response::new();
+        let mut processed = 0;
+        while processed < limit {
+            let next = SUBSCRIPTION_QUEUE
+                .keys(deps.storage, None, Some(due.clone()), Order::Ascending)
+                .next()
+                .transpose()?;
+            let (due_at, id) = match next {
+                Some(key) => key,
+                None => break,
+            };
+            processed += 1;
+
+            let mut subscription = SUBSCRIPTIONS.load(deps.storage, id)?;
+            if !donor_allowed(deps.as_ref(), &subscription.donor)? {
+                let refund = end_subscription(deps.storage, id, &subscription)?;
+                resp = resp
+                    .add_message(BankMsg::Send {
+                        to_address: subscription.donor.to_string(),
+                        amount: vec![refund],
+                    })
+                    .add_attribute("ended_subscription_id", id.to_string());
+                continue;
+            }
+
+            SUBSCRIPTION_QUEUE.remove(deps.storage, (due_at, id));
+            let escrowed = ESCROWED.may_load(deps.storage)?.unwrap_or_default();
+            ESCROWED.save(
+                deps.storage,
+                &super::sub_coins(&escrowed, &[subscription.amount.clone()]),
+            )?;
+
+            subscription.remaining -= 1;
+            if subscription.remaining == 0 {
+                SUBSCRIPTIONS.remove(deps.storage, id);
+            } else {
+                subscription.next_due = subscription.next_due.plus_seconds(subscription.period);
+                SUBSCRIPTIONS.save(deps.storage, id, &subscription)?;
+                SUBSCRIPTION_QUEUE.save(
+                    deps.storage,
+                    (subscription.next_due.nanos(), id),
+                    &Empty {},
+                )?;
+            }
+
+            let donation = donate(
+                deps.branch(),
+                env.clone(),
+                MessageInfo {
+                    sender: subscription.donor,
+                    funds: vec![subscription.amount],
+                },
+                None,
+                None,
+                None,
+            )?;
+            resp = resp
+                .add_submessages(donation.messages)
+                .add_events(donation.events);
+        }
+
+        let resp = resp
+            .add_attribute("action", "process_subscriptions")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("processed", processed.to_string());
+        Ok(resp)
+    }
+
+    pub fn update_flush_bounty(
+        deps: DepsMut,
+        info: MessageInfo,
+        bounty: Option<Decimal>,
+    ) -> Result<Response, ContractError> {
+        ensure_owner(deps.as_ref(), &info.sender)?;
+
+        match bounty {
+            Some(bounty) if bounty >= Decimal::one() => {
+                return Err(ContractError::InvalidFlushBounty {});
+            }
+            Some(bounty) => FLUSH_BOUNTY.save(deps.storage, &bounty)?,
+            None => FLUSH_BOUNTY.remove(deps.storage),
+        }
+
+        let resp = Response::new()
+            .add_attribute("action", "update_flush_bounty")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn flush_to_parent(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        counter_id: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let id = counter_id.as_deref().unwrap_or(DEFAULT_COUNTER_ID);
+        let mut state = COUNTERS.load(deps.storage, id)?;
+        let parent_donation = state
+            .parent
+            .clone()
+            .ok_or(ContractError::NoPendingForward {})?;
+
+        // Parents donated to by time or height can be flushed to as soon as the interval passed
+        let pending = PENDING_FORWARDS.has(deps.storage, id);
+        let eligible = match parent_donation.interval {
+            Some(interval) => match super::next_forward(deps.storage, id, interval)? {
+                Some(next) => next.is_expired(&env.block),
+                None => true,
+            },
+            None => false,
+        };
+        if !pending && !eligible {
+            return Err(ContractError::NoPendingForward {});
+        }
+        PENDING_FORWARDS.remove(deps.storage, id);
+        super::start_forward_interval(deps.storage, id, &env.block)?;
+
+        let rate = FLUSH_BOUNTY.may_load(deps.storage)?.unwrap_or_default();
+        let share = if id == DEFAULT_COUNTER_ID {
+            parent_share(deps.as_ref(), &env, &parent_donation, &[])?
+        } else {
+            let share = named_parent_share(&state, &parent_donation);
+            state.balance = super::sub_coins(&state.balance, &share);
+            COUNTERS.save(deps.storage, id, &state)?;
+
+            let named = NAMED_BALANCE.may_load(deps.storage)?.unwrap_or_default();
+            NAMED_BALANCE.save(deps.storage, &super::sub_coins(&named, &share))?;
+            share
+        };
+        let bounty: Vec<_> = share
+            .iter()
+            .map(|coin| Coin {
+                denom: coin.denom.clone(),
+                amount: coin.amount * rate,
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+        let funds: Vec<_> = super::sub_coins(&share, &bounty)
+            .into_iter()
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+
+        let event = events::parent_forward_event(&parent_donation.address, &funds);
+        let msgs = forward_to_parent(deps.branch(), &env, id, &parent_donation, funds)?;
+
+        let mut resp = Response::new()
+            .add_submessages(msgs)
+            .add_event(event)
+            .add_attribute("action", "flush_to_parent")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("donated_to_parent", parent_donation.address.to_string());
+        if id != DEFAULT_COUNTER_ID {
+            resp = resp.add_attribute("counter_id", id);
+        }
+        if !bounty.is_empty() {
+            let mut paid = BOUNTIES_PAID.may_load(deps.storage)?.unwrap_or_default();
+            super::add_coins(&mut paid, &bounty);
+            BOUNTIES_PAID.save(deps.storage, &paid)?;
+            resp = resp.add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: bounty,
+            });
+        }
+
+        Ok(resp)
+    }
 }
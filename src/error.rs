@@ -1,5 +1,5 @@
+use cosmwasm_std::{Coin, Decimal, StdError};
 use thiserror::Error;
-use cosmwasm_std::StdError;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
@@ -13,5 +13,167 @@ pub enum ContractError {
     InvalidName(String),
 
     #[error("Migrating from unsupported version: {0}")]
-    InvalidVersion(String)
+    InvalidVersion(String),
+
+    #[error("Parent {0} is not a counting contract")]
+    InvalidParent(String),
+
+    #[error("Parent chain loops back to {addr}")]
+    ParentCycle { addr: String },
+
+    #[error("{addr} is not a child of this contract")]
+    NotChild { addr: String },
+
+    #[error("Invalid memo: {0}")]
+    InvalidMemo(String),
+
+    #[error("{addr} is not allowed to donate")]
+    DonorNotAllowed { addr: String },
+
+    #[error("Allowance is expired")]
+    AllowanceExpired {},
+
+    #[error("Insufficient allowance, only {allowance} left")]
+    InsufficientAllowance { allowance: Coin },
+
+    #[error("Cannot withdraw a zero amount")]
+    ZeroWithdrawal {},
+
+    #[error("Invalid expiration, it has already passed")]
+    InvalidExpiration {},
+
+    #[error("Amount exceeds what is vested, only {available} can be withdrawn")]
+    ExceedsVested { available: Coin },
+
+    #[error("Vesting has to end after it starts")]
+    InvalidVesting {},
+
+    #[error("Beneficiary shares must add up to exactly 1, got {total}")]
+    InvalidShares { total: Decimal },
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Beneficiaries are owed {owed}, more than the contract holds")]
+    OwedExceedsBalance { owed: Coin },
+
+    #[error("Unauthorized - only the fee admin can change the fee")]
+    NotFeeAdmin {},
+
+    #[error("Fee rate {rate} is above 1")]
+    InvalidFeeRate { rate: Decimal },
+
+    #[error("Invalid counter id: {0}")]
+    InvalidCounterId(String),
+
+    #[error("Counter {id} already exists")]
+    CounterExists { id: String },
+
+    #[error("Amount exceeds the counter balance, only {available} can be withdrawn")]
+    ExceedsBalance { available: Coin },
+
+    #[error("Only unordered IBC channels are supported")]
+    InvalidIbcOrder {},
+
+    #[error("Invalid IBC channel version: {version}")]
+    InvalidIbcVersion { version: String },
+
+    #[error("Unknown IBC channel: {channel}")]
+    UnknownChannel { channel: String },
+
+    #[error("IBC counterparty {port} on {connection} is not approved")]
+    UnapprovedCounterparty { connection: String, port: String },
+
+    #[error("Counter overflow")]
+    CounterOverflow {},
+
+    #[error("No counted donations to sync")]
+    NothingToSync {},
+
+    #[error("Remote parent transfer timeout must be positive")]
+    InvalidTransferTimeout {},
+
+    #[error("IBC support is not built into this contract")]
+    IbcDisabled {},
+
+    #[error("Remote parents need ibc-hooks support, enable it with SetIbcHooks")]
+    IbcHooksDisabled {},
+
+    #[error("Counter {id} still has a remote parent")]
+    RemoteParentInUse { id: String },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReply { id: u64 },
+
+    #[error("Missing sequence in transfer response")]
+    InvalidTransferResponse {},
+
+    #[error("Only the receipt token can be received, not {token}")]
+    UnknownToken { token: String },
+
+    #[error("Receipt tokens can't be redeemed")]
+    RedeemDisabled {},
+
+    #[error("Withdrawals from the default counter are decided by donor governance")]
+    GovernanceEnabled {},
+
+    #[error("Donor governance is not enabled")]
+    GovernanceDisabled {},
+
+    #[error("Quorum and threshold must be above 0 and at most 1")]
+    InvalidGovernanceConfig {},
+
+    #[error("Proposal must send some funds")]
+    EmptyProposal {},
+
+    #[error("No voting weight at the proposal start")]
+    NoVotingWeight {},
+
+    #[error("Already voted on proposal {id}")]
+    AlreadyVoted { id: u64 },
+
+    #[error("Proposal {id} is not open for voting")]
+    ProposalNotOpen { id: u64 },
+
+    #[error("Proposal {id} has not passed")]
+    ProposalNotPassed { id: u64 },
+
+    #[error("The owner is a multisig, propose the action and have the members approve it")]
+    MultisigApprovalRequired {},
+
+    #[error("The owner is not a multisig")]
+    MultisigDisabled {},
+
+    #[error("Multisig members must be unique and the threshold between 1 and their number")]
+    InvalidMultisig {},
+
+    #[error("Not a multisig member")]
+    NotMultisigMember {},
+
+    #[error("Already approved owner action {id}")]
+    AlreadyApproved { id: u64 },
+
+    #[error("Owner action {id} was already executed")]
+    OwnerActionExecuted { id: u64 },
+
+    #[error("Only messages restricted to the owner can be proposed as owner actions")]
+    InvalidOwnerAction {},
+
+    #[error("Cannot withdraw to the contract itself")]
+    WithdrawToSelf {},
+
+    #[error("Subscription amount, period and number of periods must be positive")]
+    InvalidSubscription {},
+
+    #[error("Subscription deposit must be exactly {expected}")]
+    InvalidSubscriptionDeposit { expected: Coin },
+
+    #[error("Only the donor can cancel subscription {id}")]
+    NotSubscriber { id: u64 },
+
+    #[error("Flush bounty must be below 1")]
+    InvalidFlushBounty {},
+
+    #[error("No forward to the parent is pending")]
+    NoPendingForward {},
 }
\ No newline at end of file
@@ -0,0 +1,83 @@
+//! Wasm events emitted by the contract. Event types and attribute keys defined here are part of
+//! the contract interface, so indexers can rely on them instead of the loose `action`
+//! attributes.
+
+use cosmwasm_std::{Addr, Coin, Event};
+
+pub const DONATE_EVENT: &str = "counter_donate";
+pub const RESET_EVENT: &str = "counter_reset";
+pub const WITHDRAW_EVENT: &str = "counter_withdraw";
+pub const PARENT_FORWARD_EVENT: &str = "parent_forward";
+/// Transfer to a remote parent which failed or timed out, its funds back in the counter.
+pub const PARENT_REFUND_EVENT: &str = "parent_refund";
+
+pub const SENDER_KEY: &str = "sender";
+pub const DONOR_KEY: &str = "donor";
+pub const RECIPIENT_KEY: &str = "recipient";
+pub const PARENT_KEY: &str = "parent";
+pub const COUNTER_KEY: &str = "counter";
+/// Id of the counter acted on, `default` for the default counter.
+pub const COUNTER_ID_KEY: &str = "counter_id";
+pub const COUNTED_KEY: &str = "counted";
+pub const DONATION_ID_KEY: &str = "donation_id";
+/// Comma separated coins, e.g. `10atom,5osmo`. Left out with no coins, as attribute values
+/// can't be empty.
+pub const AMOUNT_KEY: &str = "amount";
+/// Comma separated denoms of `amount`, in the same order.
+pub const DENOMS_KEY: &str = "denoms";
+
+fn with_amount(event: Event, amount: &[Coin]) -> Event {
+    if amount.is_empty() {
+        return event;
+    }
+
+    let coins: Vec<_> = amount.iter().map(Coin::to_string).collect();
+    let denoms: Vec<_> = amount.iter().map(|coin| coin.denom.as_str()).collect();
+
+    event
+        .add_attribute(AMOUNT_KEY, coins.join(","))
+        .add_attribute(DENOMS_KEY, denoms.join(","))
+}
+
+pub fn donate_event(
+    sender: &Addr,
+    donor: &Addr,
+    amount: &[Coin],
+    counted: bool,
+    counter_id: &str,
+    counter: u64,
+    donation_id: u64,
+) -> Event {
+    let event = Event::new(DONATE_EVENT)
+        .add_attribute(SENDER_KEY, sender.as_str())
+        .add_attribute(DONOR_KEY, donor.as_str())
+        .add_attribute(COUNTED_KEY, counted.to_string())
+        .add_attribute(COUNTER_ID_KEY, counter_id)
+        .add_attribute(COUNTER_KEY, counter.to_string())
+        .add_attribute(DONATION_ID_KEY, donation_id.to_string());
+    with_amount(event, amount)
+}
+
+pub fn reset_event(sender: &Addr, counter_id: &str, counter: u64) -> Event {
+    Event::new(RESET_EVENT)
+        .add_attribute(SENDER_KEY, sender.as_str())
+        .add_attribute(COUNTER_ID_KEY, counter_id)
+        .add_attribute(COUNTER_KEY, counter.to_string())
+}
+
+pub fn withdraw_event(sender: &Addr, recipient: &Addr, amount: &[Coin]) -> Event {
+    let event = Event::new(WITHDRAW_EVENT)
+        .add_attribute(SENDER_KEY, sender.as_str())
+        .add_attribute(RECIPIENT_KEY, recipient.as_str());
+    with_amount(event, amount)
+}
+
+pub fn parent_forward_event(parent: &Addr, amount: &[Coin]) -> Event {
+    let event = Event::new(PARENT_FORWARD_EVENT).add_attribute(PARENT_KEY, parent.as_str());
+    with_amount(event, amount)
+}
+
+pub fn parent_refund_event(parent: &Addr, amount: &[Coin]) -> Event {
+    let event = Event::new(PARENT_REFUND_EVENT).add_attribute(PARENT_KEY, parent.as_str());
+    with_amount(event, amount)
+}
@@ -0,0 +1,83 @@
+//! Companion contract instantiating counting contracts and keeping track of them. It is built
+//! into the same crate with the `factory` feature, which exports its entry points instead of
+//! the counting contract ones.
+
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Reply, Response, StdResult,
+};
+
+#[cfg(all(feature = "factory", not(feature = "library")))]
+use cosmwasm_std::entry_point;
+use error::FactoryError;
+use msg::{ExecMsg, InstantiateMsg, QueryMsg};
+
+pub(crate) mod contract;
+pub mod error;
+pub mod msg;
+mod state;
+
+#[cfg_attr(all(feature = "factory", not(feature = "library")), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    contract::instantiate(deps, info, msg.counter_code_id)
+}
+
+#[cfg_attr(all(feature = "factory", not(feature = "library")), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    use QueryMsg::*;
+
+    match msg {
+        Config {} => to_json_binary(&contract::query::config(deps)?),
+        CountersByCreator {
+            creator,
+            start_after,
+            limit,
+        } => to_json_binary(&contract::query::counters_by_creator(
+            deps,
+            creator,
+            start_after,
+            limit,
+        )?),
+        AllCounters { start_after, limit } => {
+            to_json_binary(&contract::query::all_counters(deps, start_after, limit)?)
+        }
+        PredictAddress { creator, label } => to_json_binary(&contract::query::predict_address(
+            deps, env, creator, label,
+        )?),
+    }
+}
+
+#[cfg_attr(all(feature = "factory", not(feature = "library")), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecMsg,
+) -> Result<Response, FactoryError> {
+    use ExecMsg::*;
+
+    match msg {
+        CreateCounter { msg, label } => contract::exec::create_counter(deps, env, info, msg, label),
+        CreateCounter2 { msg, label } => {
+            contract::exec::create_counter2(deps, env, info, msg, label)
+        }
+        UpdateCodeId { code_id } => contract::exec::update_code_id(deps, info, code_id),
+        MigrateCounters { start_after, limit } => {
+            contract::exec::migrate_counters(deps, info, start_after, limit)
+        }
+    }
+}
+
+#[cfg_attr(all(feature = "factory", not(feature = "library")), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, FactoryError> {
+    contract::reply(deps, reply)
+}
+
+#[cfg_attr(all(feature = "factory", not(feature = "library")), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> StdResult<Response> {
+    contract::migrate(deps)
+}
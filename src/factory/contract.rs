@@ -0,0 +1,322 @@
+use cosmwasm_std::{
+    instantiate2_address, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Reply, Response,
+    StdError, StdResult,
+};
+use cw2::set_contract_version;
+use cw_utils::parse_reply_instantiate_data;
+use sha2::{Digest, Sha256};
+
+use super::{
+    error::FactoryError,
+    state::{Config, CONFIG, COUNTERS, CREATED, PENDING_CREATOR},
+};
+
+const CONTRACT_NAME: &str = concat!(env!("CARGO_PKG_NAME"), "_factory");
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const DEFAULT_PAGE_LIMIT: u32 = 10;
+const MAX_PAGE_LIMIT: u32 = 30;
+
+pub const INSTANTIATE_COUNTER_REPLY_ID: u64 = 1;
+
+pub fn instantiate(deps: DepsMut, info: MessageInfo, counter_code_id: u64) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            owner: info.sender,
+            counter_code_id,
+        },
+    )?;
+
+    Ok(Response::new())
+}
+
+/// Salt for `Instantiate2` of a counter created by `creator` with `label`.
+pub fn counter_salt(creator: &Addr, label: &str) -> Binary {
+    let mut hasher = Sha256::new();
+    hasher.update((creator.as_str().len() as u64).to_be_bytes());
+    hasher.update(creator.as_bytes());
+    hasher.update(label.as_bytes());
+    Binary::from(hasher.finalize().to_vec())
+}
+
+/// Address a counter instantiated by this factory through `Instantiate2` with `salt` gets.
+pub fn predict_address(deps: Deps, env: &Env, code_id: u64, salt: &[u8]) -> StdResult<Addr> {
+    let checksum = deps.querier.query_wasm_code_info(code_id)?.checksum;
+    let factory = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+    let addr = instantiate2_address(checksum.as_slice(), &factory, salt)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    deps.api.addr_humanize(&addr)
+}
+
+pub fn reply(deps: DepsMut, reply: Reply) -> Result<Response, FactoryError> {
+    if reply.id != INSTANTIATE_COUNTER_REPLY_ID {
+        return Err(FactoryError::UnknownReply { id: reply.id });
+    }
+
+    let instantiated = parse_reply_instantiate_data(reply)?;
+    let counter = deps.api.addr_validate(&instantiated.contract_address)?;
+    let creator = PENDING_CREATOR.load(deps.storage)?;
+    PENDING_CREATOR.remove(deps.storage);
+
+    COUNTERS.save(deps.storage, &counter, &creator)?;
+    CREATED.save(deps.storage, (&creator, &counter), &Empty {})?;
+
+    let resp = Response::new()
+        .add_attribute("action", "counter_created")
+        .add_attribute("creator", creator.as_str())
+        .add_attribute("counter", counter.as_str());
+    Ok(resp)
+}
+
+pub fn migrate(deps: DepsMut) -> StdResult<Response> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new())
+}
+
+pub mod query {
+    use cosmwasm_std::{Deps, Env, Order, StdResult};
+    use cw_storage_plus::Bound;
+
+    use crate::factory::{
+        msg::{ConfigResp, CounterEntry, CountersResp, PredictAddressResp},
+        state::{CONFIG, COUNTERS, CREATED},
+    };
+
+    pub fn config(deps: Deps) -> StdResult<ConfigResp> {
+        let config = CONFIG.load(deps.storage)?;
+        Ok(ConfigResp {
+            owner: config.owner,
+            counter_code_id: config.counter_code_id,
+        })
+    }
+
+    pub fn counters_by_creator(
+        deps: Deps,
+        creator: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<CountersResp> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+        let creator = deps.api.addr_validate(&creator)?;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let counters = CREATED
+            .prefix(&creator)
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|addr| {
+                addr.map(|addr| CounterEntry {
+                    addr,
+                    creator: creator.clone(),
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(CountersResp { counters })
+    }
+
+    pub fn all_counters(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<CountersResp> {
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let counters = COUNTERS
+            .range(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|counter| counter.map(|(addr, creator)| CounterEntry { addr, creator }))
+            .collect::<StdResult<_>>()?;
+
+        Ok(CountersResp { counters })
+    }
+
+    pub fn predict_address(
+        deps: Deps,
+        env: Env,
+        creator: String,
+        label: String,
+    ) -> StdResult<PredictAddressResp> {
+        let creator = deps.api.addr_validate(&creator)?;
+        let code_id = CONFIG.load(deps.storage)?.counter_code_id;
+
+        let salt = super::counter_salt(&creator, &label);
+        let addr = super::predict_address(deps, &env, code_id, &salt)?;
+
+        Ok(PredictAddressResp { salt, addr })
+    }
+}
+
+pub mod exec {
+    use cosmwasm_std::{
+        to_json_binary, Addr, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdResult,
+        SubMsg, WasmMsg,
+    };
+    use cw_storage_plus::Bound;
+
+    use crate::factory::{
+        error::FactoryError,
+        state::{Config, CONFIG, COUNTERS, PENDING_CREATOR},
+    };
+    use crate::msg::InstantiateMsg;
+
+    fn ensure_owner(deps: Deps, sender: &Addr) -> Result<Config, FactoryError> {
+        let config = CONFIG.load(deps.storage)?;
+        if *sender != config.owner {
+            return Err(FactoryError::Unauthorized {
+                owner: config.owner.into_string(),
+            });
+        }
+
+        Ok(config)
+    }
+
+    pub fn create_counter(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        mut msg: InstantiateMsg,
+        label: String,
+    ) -> Result<Response, FactoryError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        if msg.owner.is_none() {
+            msg.owner = Some(info.sender.to_string());
+        }
+        PENDING_CREATOR.save(deps.storage, &info.sender)?;
+
+        let instantiate = WasmMsg::Instantiate {
+            admin: Some(env.contract.address.into_string()),
+            code_id: config.counter_code_id,
+            msg: to_json_binary(&msg)?,
+            funds: info.funds,
+            label,
+        };
+
+        let resp = Response::new()
+            .add_submessage(SubMsg::reply_on_success(
+                instantiate,
+                super::INSTANTIATE_COUNTER_REPLY_ID,
+            ))
+            .add_attribute("action", "create_counter")
+            .add_attribute("sender", info.sender.as_str());
+        Ok(resp)
+    }
+
+    pub fn create_counter2(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        mut msg: InstantiateMsg,
+        label: String,
+    ) -> Result<Response, FactoryError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        if msg.owner.is_none() {
+            msg.owner = Some(info.sender.to_string());
+        }
+        PENDING_CREATOR.save(deps.storage, &info.sender)?;
+
+        let salt = super::counter_salt(&info.sender, &label);
+        let predicted = super::predict_address(deps.as_ref(), &env, config.counter_code_id, &salt)?;
+
+        let instantiate = WasmMsg::Instantiate2 {
+            admin: Some(env.contract.address.into_string()),
+            code_id: config.counter_code_id,
+            label,
+            msg: to_json_binary(&msg)?,
+            funds: info.funds,
+            salt,
+        };
+
+        let resp = Response::new()
+            .add_submessage(SubMsg::reply_on_success(
+                instantiate,
+                super::INSTANTIATE_COUNTER_REPLY_ID,
+            ))
+            .add_attribute("action", "create_counter2")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("predicted_address", predicted.as_str());
+        Ok(resp)
+    }
+
+    pub fn update_code_id(
+        deps: DepsMut,
+        info: MessageInfo,
+        code_id: u64,
+    ) -> Result<Response, FactoryError> {
+        let mut config = ensure_owner(deps.as_ref(), &info.sender)?;
+
+        config.counter_code_id = code_id;
+        CONFIG.save(deps.storage, &config)?;
+
+        let resp = Response::new()
+            .add_attribute("action", "update_code_id")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("code_id", code_id.to_string());
+        Ok(resp)
+    }
+
+    pub fn migrate_counters(
+        deps: DepsMut,
+        info: MessageInfo,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Response, FactoryError> {
+        let config = ensure_owner(deps.as_ref(), &info.sender)?;
+
+        let limit = limit
+            .unwrap_or(super::DEFAULT_PAGE_LIMIT)
+            .min(super::MAX_PAGE_LIMIT) as usize;
+        let start_after = start_after
+            .map(|addr| deps.api.addr_validate(&addr))
+            .transpose()?;
+
+        let counters = COUNTERS
+            .keys(
+                deps.storage,
+                start_after.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let msg = to_json_binary(&Empty {})?;
+        let migrations = counters.iter().map(|counter| WasmMsg::Migrate {
+            contract_addr: counter.to_string(),
+            new_code_id: config.counter_code_id,
+            msg: msg.clone(),
+        });
+
+        let mut resp = Response::new()
+            .add_messages(migrations)
+            .add_attribute("action", "migrate_counters")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("migrated", counters.len().to_string());
+        if let Some(last) = counters.last() {
+            resp = resp.add_attribute("last", last.as_str());
+        }
+        Ok(resp)
+    }
+}
@@ -0,0 +1,18 @@
+use cosmwasm_std::StdError;
+use cw_utils::ParseReplyError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FactoryError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("Unauthorized - only {owner} can call it")]
+    Unauthorized { owner: String },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReply { id: u64 },
+}
@@ -0,0 +1,76 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Addr, Binary};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub counter_code_id: u64,
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(ConfigResp)]
+    Config {},
+    #[returns(CountersResp)]
+    CountersByCreator {
+        creator: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(CountersResp)]
+    AllCounters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Address `CreateCounter2` from `creator` with `label` instantiates at.
+    #[returns(PredictAddressResp)]
+    PredictAddress { creator: String, label: String },
+}
+
+#[cw_serde]
+pub struct ConfigResp {
+    pub owner: Addr,
+    pub counter_code_id: u64,
+}
+
+#[cw_serde]
+pub struct CounterEntry {
+    pub addr: Addr,
+    pub creator: Addr,
+}
+
+#[cw_serde]
+pub struct CountersResp {
+    pub counters: Vec<CounterEntry>,
+}
+
+#[cw_serde]
+pub struct PredictAddressResp {
+    pub salt: Binary,
+    pub addr: Addr,
+}
+
+#[cw_serde]
+pub enum ExecMsg {
+    /// Instantiates a counting contract with the factory as its admin. Unless `msg` sets an
+    /// owner, the sender owns the new counter. Sent funds are passed on to it.
+    CreateCounter {
+        msg: crate::msg::InstantiateMsg,
+        label: String,
+    },
+    /// Same as `CreateCounter`, but through `Instantiate2` with a salt derived from the sender
+    /// and `label`, so the address is known upfront. That allows creating a parent and a child
+    /// pointing to it in a single transaction.
+    CreateCounter2 {
+        msg: crate::msg::InstantiateMsg,
+        label: String,
+    },
+    UpdateCodeId {
+        code_id: u64,
+    },
+    /// Migrates a page of created counters to the current code id.
+    MigrateCounters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
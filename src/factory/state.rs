@@ -0,0 +1,19 @@
+use cosmwasm_std::{Addr, Empty};
+use cw_storage_plus::{Item, Map};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub owner: Addr,
+    pub counter_code_id: u64,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Creator of the counter being instantiated, until its reply comes back.
+pub const PENDING_CREATOR: Item<Addr> = Item::new("pending_creator");
+
+/// Every created counter with its creator.
+pub const COUNTERS: Map<&Addr, Addr> = Map::new("counters");
+/// Same registry keyed by creator first.
+pub const CREATED: Map<(&Addr, &Addr), Empty> = Map::new("created");
@@ -0,0 +1,208 @@
+//! IBC entry points letting counting contracts on different chains share their counts. Each
+//! side sends the donations it counted with `SyncToRemote`, and the receiving contract adds them
+//! to its default counter.
+
+#[cfg(not(any(feature = "library", feature = "factory")))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_json, to_json_binary, Binary, Deps, DepsMut, Env, Ibc3ChannelOpenResponse,
+    IbcBasicResponse, IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
+    IbcChannelOpenResponse, IbcOrder, IbcPacket, IbcPacketAckMsg, IbcPacketReceiveMsg,
+    IbcPacketTimeoutMsg, IbcReceiveResponse, StdResult,
+};
+
+use crate::{
+    contract,
+    error::ContractError,
+    msg::{CounterPacket, PacketAck},
+    state::{COUNTERS, DEFAULT_COUNTER_ID, IBC_CHANNELS, IBC_COUNTERPARTIES, SYNCED},
+};
+
+pub const IBC_VERSION: &str = "counting-1";
+pub const IBC_ORDER: IbcOrder = IbcOrder::Unordered;
+/// Seconds a sent packet may wait for relaying before it times out.
+pub const PACKET_LIFETIME: u64 = 60 * 60;
+
+/// Checks the channel settings, and that the owner approved the counterparty.
+fn validate_channel(
+    deps: Deps,
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    if channel.order != IBC_ORDER {
+        return Err(ContractError::InvalidIbcOrder {});
+    }
+
+    if channel.version != IBC_VERSION {
+        return Err(ContractError::InvalidIbcVersion {
+            version: channel.version.clone(),
+        });
+    }
+
+    if let Some(version) = counterparty_version {
+        if version != IBC_VERSION {
+            return Err(ContractError::InvalidIbcVersion {
+                version: version.to_owned(),
+            });
+        }
+    }
+
+    let counterparty = (
+        channel.connection_id.as_str(),
+        channel.counterparty_endpoint.port_id.as_str(),
+    );
+    if !IBC_COUNTERPARTIES.has(deps.storage, counterparty) {
+        return Err(ContractError::UnapprovedCounterparty {
+            connection: channel.connection_id.clone(),
+            port: channel.counterparty_endpoint.port_id.clone(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn ibc_channel_open(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    validate_channel(deps.as_ref(), msg.channel(), msg.counterparty_version())?;
+
+    Ok(Some(Ibc3ChannelOpenResponse {
+        version: IBC_VERSION.to_owned(),
+    }))
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    validate_channel(deps.as_ref(), channel, msg.counterparty_version())?;
+
+    IBC_CHANNELS.save(
+        deps.storage,
+        &channel.endpoint.channel_id,
+        &channel.counterparty_endpoint,
+    )?;
+
+    let resp = IbcBasicResponse::new()
+        .add_attribute("action", "ibc_channel_connect")
+        .add_attribute("channel", channel.endpoint.channel_id.as_str());
+    Ok(resp)
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = &msg.channel().endpoint.channel_id;
+
+    IBC_CHANNELS.remove(deps.storage, channel);
+    SYNCED.remove(deps.storage, channel);
+
+    let resp = IbcBasicResponse::new()
+        .add_attribute("action", "ibc_channel_close")
+        .add_attribute("channel", channel.as_str());
+    Ok(resp)
+}
+
+/// Never fails, so the sender always gets an acknowledgement. Errors are reported in it instead,
+/// and the sender rolls the sync back.
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let resp = match receive(deps, env, &msg.packet) {
+        Ok(resp) => resp,
+        Err(err) => IbcReceiveResponse::new()
+            .set_ack(to_json_binary(&PacketAck::Error(err.to_string()))?)
+            .add_attribute("action", "ibc_packet_receive")
+            .add_attribute("error", err.to_string()),
+    };
+    Ok(resp)
+}
+
+fn receive(
+    deps: DepsMut,
+    env: Env,
+    packet: &IbcPacket,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let CounterPacket::Increment { by } = from_json(&packet.data)?;
+
+    let mut state = COUNTERS.load(deps.storage, DEFAULT_COUNTER_ID)?;
+    state.counter = state
+        .counter
+        .checked_add(by)
+        .ok_or(ContractError::CounterOverflow {})?;
+    COUNTERS.save(deps.storage, DEFAULT_COUNTER_ID, &state)?;
+    contract::checkpoint_counter(
+        deps.storage,
+        DEFAULT_COUNTER_ID,
+        env.block.height,
+        state.counter,
+    )?;
+
+    let resp = IbcReceiveResponse::new()
+        .set_ack(to_json_binary(&PacketAck::Result(Binary::default()))?)
+        .add_attribute("action", "ibc_packet_receive")
+        .add_attribute("channel", packet.dest.channel_id.as_str())
+        .add_attribute("by", by.to_string())
+        .add_attribute("counter", state.counter.to_string());
+    Ok(resp)
+}
+
+/// Makes donations of a packet which didn't make it count again in the next sync.
+fn rollback_sync(deps: DepsMut, packet: &IbcPacket) -> StdResult<u64> {
+    let CounterPacket::Increment { by } = from_json(&packet.data)?;
+    SYNCED.update(
+        deps.storage,
+        &packet.src.channel_id,
+        |synced| -> StdResult<_> { Ok(synced.unwrap_or_default().saturating_sub(by)) },
+    )?;
+    Ok(by)
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let resp = IbcBasicResponse::new()
+        .add_attribute("action", "ibc_packet_ack")
+        .add_attribute("channel", msg.original_packet.src.channel_id.as_str());
+
+    match from_json(&msg.acknowledgement.data)? {
+        PacketAck::Result(_) => Ok(resp.add_attribute("success", "true")),
+        PacketAck::Error(err) => {
+            let by = rollback_sync(deps, &msg.original_packet)?;
+            Ok(resp
+                .add_attribute("success", "false")
+                .add_attribute("error", err)
+                .add_attribute("rolled_back", by.to_string()))
+        }
+    }
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let by = rollback_sync(deps, &msg.packet)?;
+
+    let resp = IbcBasicResponse::new()
+        .add_attribute("action", "ibc_packet_timeout")
+        .add_attribute("channel", msg.packet.src.channel_id.as_str())
+        .add_attribute("rolled_back", by.to_string());
+    Ok(resp)
+}
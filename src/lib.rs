@@ -1,37 +1,129 @@
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Empty};
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
+};
 
-#[cfg(not(feature = "library"))]
+#[cfg(not(any(feature = "library", feature = "factory")))]
 use cosmwasm_std::entry_point;
+#[cfg(feature = "ibc")]
+use cosmwasm_std::Reply;
 use error::ContractError;
 use msg::{ExecMsg, InstantiateMsg};
+#[cfg(feature = "ibc")]
+use msg::SudoMsg;
 
 mod contract;
 pub mod error;
+pub mod events;
+#[cfg(feature = "factory")]
+pub mod factory;
+#[cfg(feature = "ibc")]
+pub mod ibc;
 pub mod msg;
 #[cfg(any(test, feature = "tests"))]
 pub mod multitest;
+#[cfg(feature = "ibc")]
+mod proto;
 mod state;
+#[cfg(feature = "ibc")]
+mod transfer;
 
-#[cfg_attr(not(feature = "library"), entry_point)]
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
-    contract::instantiate(deps, info, msg.counter, msg.minimal_donation, msg.parent)
+) -> Result<Response, ContractError> {
+    contract::instantiate(deps, env, info, msg)
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     use msg::QueryMsg::*;
 
     match msg {
-        Value {} => to_binary(&contract::query::value(deps)?),
+        Value {} => to_json_binary(&contract::query::value(deps)?),
+        ValueAtHeight { height, counter_id } => {
+            to_json_binary(&contract::query::value_at_height(deps, height, counter_id)?)
+        }
+        CheckpointPruning {} => to_json_binary(&contract::query::checkpoint_pruning(deps)?),
+        Parent {} => to_json_binary(&contract::query::parent(deps, env)?),
+        Ancestors { limit } => to_json_binary(&contract::query::ancestors(deps, env, limit)?),
+        Children {} => to_json_binary(&contract::query::children(deps)?),
+        ParentStats {} => to_json_binary(&contract::query::parent_stats(deps)?),
+        Donation { id } => to_json_binary(&contract::query::donation(deps, id)?),
+        Donor { addr } => to_json_binary(&contract::query::donor(deps, addr)?),
+        ListDonors { start_after, limit } => {
+            to_json_binary(&contract::query::list_donors(deps, start_after, limit)?)
+        }
+        AccessMode {} => to_json_binary(&contract::query::access_mode(deps)?),
+        AccessList { start_after, limit } => {
+            to_json_binary(&contract::query::access_list(deps, start_after, limit)?)
+        }
+        Withdrawable {} => to_json_binary(&contract::query::withdrawable(deps, env)?),
+        Beneficiaries {} => to_json_binary(&contract::query::beneficiaries(deps)?),
+        Claimable { addr } => to_json_binary(&contract::query::claimable(deps, addr)?),
+        TotalOwed {} => to_json_binary(&contract::query::total_owed(deps)?),
+        Fee {} => to_json_binary(&contract::query::fee(deps)?),
+        Allowance { spender, denom } => {
+            to_json_binary(&contract::query::allowance(deps, spender, denom)?)
+        }
+        AllAllowances {
+            spender,
+            start_after,
+            limit,
+        } => to_json_binary(&contract::query::all_allowances(
+            deps,
+            spender,
+            start_after,
+            limit,
+        )?),
+        ListCounters { start_after, limit } => to_json_binary(&contract::query::list_counters(
+            deps,
+            env,
+            start_after,
+            limit,
+        )?),
+        Receipt {} => to_json_binary(&contract::query::receipt(deps)?),
+        Governance {} => to_json_binary(&contract::query::governance(deps)?),
+        Multisig {} => to_json_binary(&contract::query::multisig(deps)?),
+        OwnerAction { id } => to_json_binary(&contract::query::owner_action(deps, id)?),
+        VotingWeight { addr } => to_json_binary(&contract::query::voting_weight(deps, addr)?),
+        Proposal { id } => to_json_binary(&contract::query::proposal(deps, env, id)?),
+        ListProposals { start_after, limit } => to_json_binary(&contract::query::list_proposals(
+            deps,
+            env,
+            start_after,
+            limit,
+        )?),
+        Vote { id, voter } => to_json_binary(&contract::query::vote(deps, id, voter)?),
+        ListVotes {
+            id,
+            start_after,
+            limit,
+        } => to_json_binary(&contract::query::list_votes(deps, id, start_after, limit)?),
+        Subscription { id } => to_json_binary(&contract::query::subscription(deps, id)?),
+        ListSubscriptions {
+            donor,
+            start_after,
+            limit,
+        } => to_json_binary(&contract::query::list_subscriptions(
+            deps,
+            donor,
+            start_after,
+            limit,
+        )?),
+        NextDue {} => to_json_binary(&contract::query::next_due(deps)?),
+        FlushBounty { counter_id } => {
+            to_json_binary(&contract::query::flush_bounty(deps, counter_id)?)
+        }
+        Invariants {} => to_json_binary(&contract::query::invariants(deps, env)?),
+        IbcCounterparties {} => to_json_binary(&contract::query::ibc_counterparties(deps)?),
+        IbcHooks {} => to_json_binary(&contract::query::ibc_hooks(deps)?),
     }
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
@@ -41,13 +133,111 @@ pub fn execute(
     use msg::ExecMsg::*;
 
     match msg {
-        Donate {} => contract::exec::donate(deps, env, info).map_err(ContractError::from),
-        Reset { counter } => contract::exec::reset(deps, info, counter),
-        Withdraw {} => contract::exec::withdraw(deps, env, info),
+        Donate {
+            memo,
+            on_behalf_of,
+            counter_id,
+        } => contract::exec::donate(deps, env, info, memo, on_behalf_of, counter_id),
+        Reset {
+            counter,
+            counter_id,
+        } => contract::exec::reset(deps, env, info, counter, counter_id),
+        Withdraw {
+            amount,
+            counter_id,
+            recipient,
+        } => contract::exec::withdraw(deps, env, info, amount, counter_id, recipient),
+        CreateCounter {
+            id,
+            minimal_donation,
+            parent,
+            owner,
+        } => contract::exec::create_counter(deps, env, info, id, minimal_donation, parent, owner),
+        UpdateParent { parent } => contract::exec::update_parent(deps, env, info, parent),
+        RegisterChild {} => contract::exec::register_child(deps, env, info),
+        UnregisterChild {} => contract::exec::unregister_child(deps, info),
+        SetAccessMode { mode } => contract::exec::set_access_mode(deps, info, mode),
+        UpdateAccessList { add, remove } => {
+            contract::exec::update_access_list(deps, info, add, remove)
+        }
+        IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => contract::exec::increase_allowance(deps, env, info, spender, amount, expires),
+        DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => contract::exec::decrease_allowance(deps, env, info, spender, amount, expires),
+        WithdrawAllowance { amount, recipient } => {
+            contract::exec::withdraw_allowance(deps, env, info, amount, recipient)
+        }
+        SetBeneficiaries {
+            beneficiaries,
+            remainder,
+            mode,
+        } => contract::exec::set_beneficiaries(deps, info, beneficiaries, remainder, mode),
+        RemoveBeneficiaries {} => contract::exec::remove_beneficiaries(deps, info),
+        Claim {} => contract::exec::claim(deps, env, info),
+        UpdateFee { fee } => contract::exec::update_fee(deps, info, fee),
+        UpdateFeeAdmin { admin } => contract::exec::update_fee_admin(deps, info, admin),
+        SetCheckpointPruning { pruning } => {
+            contract::exec::set_checkpoint_pruning(deps, info, pruning)
+        }
+        #[cfg(feature = "ibc")]
+        SyncToRemote { channel } => contract::exec::sync_to_remote(deps, env, info, channel),
+        #[cfg(not(feature = "ibc"))]
+        SyncToRemote { .. } => Err(ContractError::IbcDisabled {}),
+        UpdateIbcCounterparties { add, remove } => {
+            contract::exec::update_ibc_counterparties(deps, info, add, remove)
+        }
+        UpdateReceipt { receipt } => contract::exec::update_receipt(deps, info, receipt),
+        Receive(msg) => contract::exec::receive(deps, env, info, msg),
+        EnableGovernance { config } => contract::exec::enable_governance(deps, info, config),
+        Propose {
+            recipient,
+            amount,
+            description,
+        } => contract::exec::propose(deps, env, info, recipient, amount, description),
+        Vote { id, yes } => contract::exec::vote(deps, env, info, id, yes),
+        Execute { id } => contract::exec::execute_proposal(deps, env, info, id),
+        UpdateOwner { owner } => contract::exec::update_owner(deps, env, info, owner),
+        ProposeOwnerAction { action } => {
+            contract::exec::propose_owner_action(deps, env, info, *action)
+        }
+        ApproveOwnerAction { id } => contract::exec::approve_owner_action(deps, env, info, id),
+        Subscribe {
+            amount,
+            period,
+            periods,
+        } => contract::exec::subscribe(deps, env, info, amount, period, periods),
+        CancelSubscription { id } => contract::exec::cancel_subscription(deps, info, id),
+        ProcessSubscriptions { limit } => {
+            contract::exec::process_subscriptions(deps, env, info, limit)
+        }
+        UpdateFlushBounty { bounty } => contract::exec::update_flush_bounty(deps, info, bounty),
+        FlushToParent { counter_id } => {
+            contract::exec::flush_to_parent(deps, env, info, counter_id)
+        }
+        UpdateVesting { vesting } => contract::exec::update_vesting(deps, info, vesting),
+        SetIbcHooks { enabled } => contract::exec::set_ibc_hooks(deps, info, enabled),
     }
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
-    contract::migrate(deps)
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: Empty) -> Result<Response, ContractError> {
+    contract::migrate(deps, env)
+}
+
+#[cfg(feature = "ibc")]
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
+    transfer::reply(deps, reply)
+}
+
+#[cfg(feature = "ibc")]
+#[cfg_attr(not(any(feature = "library", feature = "factory")), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    transfer::sudo(deps, env, msg)
 }
@@ -1,12 +1,125 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Coin, Decimal};
-
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::{Duration, Expiration};
 
 #[cw_serde]
 pub struct Parent {
     pub addr: String,
     pub donating_period: u64,
     pub part: Decimal,
+    /// Set when the parent lives on another chain, `addr` being its address there.
+    #[serde(default)]
+    pub remote: Option<RemoteParent>,
+    /// Forwards on the first donation once this many seconds or blocks passed since the last
+    /// forward, instead of every `donating_period` donations.
+    #[serde(default)]
+    pub interval: Option<Duration>,
+}
+
+/// Parent reached through ICS-20 transfers instead of `Donate` calls. Only works on chains
+/// running ibc-hooks, which report the transfer outcomes to `sudo`, so it is rejected until the
+/// owner enables ibc-hooks support with `ibc_hooks` or `ExecMsg::SetIbcHooks`, and always
+/// without the `ibc` feature.
+#[cw_serde]
+pub struct RemoteParent {
+    /// Transfer channel on this chain leading to the parent's chain.
+    pub channel: String,
+    /// Seconds a transfer may wait for relaying before it times out and is refunded.
+    pub timeout: u64,
+}
+
+/// Who is allowed to donate: everyone, only addresses on the access list, or everyone except
+/// them. Registered child contracts are checked like any other donor.
+#[cw_serde]
+#[derive(Copy)]
+pub enum AccessMode {
+    Open,
+    AllowList,
+    DenyList,
+}
+
+/// `Push` splits owner withdrawals across beneficiaries, `Pull` credits every counted donation
+/// to them and lets them `Claim` it.
+#[cw_serde]
+#[derive(Copy)]
+pub enum PayoutMode {
+    Push,
+    Pull,
+}
+
+#[cw_serde]
+pub struct Beneficiary {
+    pub addr: String,
+    pub share: Decimal,
+}
+
+/// Releases donations to the owner over block time, either gradually between `start` and
+/// `end` or all at once `at` the cliff.
+#[cw_serde]
+pub enum VestingSchedule {
+    Linear { start: Timestamp, end: Timestamp },
+    Cliff { at: Timestamp },
+}
+
+/// Cut of every counted donation sent straight to `recipient`.
+#[cw_serde]
+pub struct FeeConfig {
+    pub recipient: String,
+    pub rate: Decimal,
+}
+
+/// cw20 token minted to donors of counted donations to the default counter, one unit per unit
+/// of the minimal donation denom. The counter must be its minter.
+#[cw_serde]
+pub struct ReceiptConfig {
+    pub token: String,
+    /// Whether holders can send the token back with `ReceiveMsg::Redeem` to burn it for their
+    /// share of the withdrawable funds. Once governance is enabled redeeming is rejected, as
+    /// withdrawals then need a passed proposal.
+    pub redeemable: bool,
+}
+
+/// Donor governance over withdrawals from the default counter. Donors vote with what they
+/// donated to it in its minimal donation denom, as of the height the proposal was made.
+#[cw_serde]
+pub struct GovernanceConfig {
+    /// Part of the total weight which has to vote for a proposal to pass.
+    pub quorum: Decimal,
+    /// Part of the votes which have to be yes for a proposal to pass. Before the voting period
+    /// ends, it is taken of the total weight instead, so the outcome can't change anymore.
+    pub threshold: Decimal,
+    pub voting_period: Duration,
+}
+
+#[cw_serde]
+pub enum Owner {
+    Single { addr: String },
+    Multisig(MultisigConfig),
+}
+
+/// N-of-M multisig owning the default counter.
+#[cw_serde]
+pub struct MultisigConfig {
+    pub members: Vec<String>,
+    pub threshold: u32,
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum ProposalStatus {
+    Open,
+    Rejected,
+    Passed,
+    Executed,
+}
+
+/// How long counter checkpoints behind `ValueAtHeight` are kept. With `KeepBlocks` only the
+/// last `blocks` blocks stay answerable, older heights may fail once pruned.
+#[cw_serde]
+pub enum CheckpointPruning {
+    Never,
+    KeepBlocks { blocks: u64 },
 }
 
 #[cw_serde]
@@ -14,6 +127,16 @@ pub struct InstantiateMsg {
     pub counter: u64,
     pub minimal_donation: Coin,
     pub parent: Option<Parent>,
+    pub parent_depth_limit: Option<u32>,
+    pub vesting: Option<VestingSchedule>,
+    pub fee: Option<FeeConfig>,
+    /// Address allowed to change the fee, the owner of the default counter if not set.
+    pub fee_admin: Option<String>,
+    /// Owner of the default counter, the instantiating address if not set.
+    pub owner: Option<String>,
+    /// Whether the chain runs ibc-hooks, see `ExecMsg::SetIbcHooks`.
+    #[serde(default)]
+    pub ibc_hooks: bool,
 }
 
 #[cw_serde]
@@ -21,6 +144,235 @@ pub struct InstantiateMsg {
 pub enum QueryMsg {
     #[returns(ValueResp)]
     Value {},
+    /// Value of counter `counter_id`, or of the default one, at the end of block `height`.
+    #[returns(ValueResp)]
+    ValueAtHeight {
+        height: u64,
+        counter_id: Option<String>,
+    },
+    #[returns(CheckpointPruningResp)]
+    CheckpointPruning {},
+    #[returns(ParentResp)]
+    Parent {},
+    #[returns(AncestorsResp)]
+    Ancestors { limit: Option<u32> },
+    #[returns(ChildrenResp)]
+    Children {},
+    #[returns(ParentStatsResp)]
+    ParentStats {},
+    #[returns(DonationResp)]
+    Donation { id: u64 },
+    #[returns(DonorResp)]
+    Donor { addr: String },
+    /// Donor stats ordered by address.
+    #[returns(ListDonorsResp)]
+    ListDonors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(AccessModeResp)]
+    AccessMode {},
+    #[returns(AccessListResp)]
+    AccessList {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(WithdrawableResp)]
+    Withdrawable {},
+    #[returns(BeneficiariesResp)]
+    Beneficiaries {},
+    #[returns(ClaimableResp)]
+    Claimable { addr: String },
+    #[returns(TotalOwedResp)]
+    TotalOwed {},
+    #[returns(FeeResp)]
+    Fee {},
+    #[returns(AllowanceResp)]
+    Allowance { spender: String, denom: String },
+    #[returns(AllAllowancesResp)]
+    AllAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(ListCountersResp)]
+    ListCounters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(ReceiptResp)]
+    Receipt {},
+    #[returns(GovernanceResp)]
+    Governance {},
+    #[returns(MultisigResp)]
+    Multisig {},
+    #[returns(OwnerActionResp)]
+    OwnerAction { id: u64 },
+    #[returns(VotingWeightResp)]
+    VotingWeight { addr: String },
+    #[returns(ProposalResp)]
+    Proposal { id: u64 },
+    #[returns(ProposalListResp)]
+    ListProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(VoteResp)]
+    Vote { id: u64, voter: String },
+    #[returns(VoteListResp)]
+    ListVotes {
+        id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(SubscriptionResp)]
+    Subscription { id: u64 },
+    /// Active subscriptions, optionally only those of `donor`.
+    #[returns(ListSubscriptionsResp)]
+    ListSubscriptions {
+        donor: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// When the earliest subscription donation is due.
+    #[returns(NextDueResp)]
+    NextDue {},
+    /// The flush bounty, and whether counter `counter_id`, or the default one, has a forward
+    /// pending.
+    #[returns(FlushBountyResp)]
+    FlushBounty { counter_id: Option<String> },
+    /// Checks the internal accounting against the contract balance, listing every violation
+    /// found.
+    #[returns(InvariantsResp)]
+    Invariants {},
+    #[returns(IbcCounterpartiesResp)]
+    IbcCounterparties {},
+    #[returns(IbcHooksResp)]
+    IbcHooks {},
+}
+
+/// Remote counting contract allowed to open channels with this one, by the connection to its
+/// chain and its port there, `wasm.<address>`.
+#[cw_serde]
+pub struct IbcCounterparty {
+    pub connection_id: String,
+    pub port_id: String,
+}
+
+#[cw_serde]
+pub struct IbcCounterpartiesResp {
+    pub counterparties: Vec<IbcCounterparty>,
+}
+
+#[cw_serde]
+pub struct IbcHooksResp {
+    pub enabled: bool,
+}
+
+#[cw_serde]
+pub struct InvariantsResp {
+    pub violations: Vec<String>,
+    /// Funds received by donations, which the donor totals add up to.
+    pub received: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct FlushBountyResp {
+    pub bounty: Option<Decimal>,
+    /// Whether a forward of the counter to its parent waits for `FlushToParent`.
+    pub pending: bool,
+}
+
+#[cw_serde]
+pub struct SubscriptionResp {
+    pub id: u64,
+    pub donor: Addr,
+    pub amount: Coin,
+    pub period: u64,
+    pub remaining: u32,
+    pub next_due: Timestamp,
+}
+
+#[cw_serde]
+pub struct ListSubscriptionsResp {
+    pub subscriptions: Vec<SubscriptionResp>,
+}
+
+#[cw_serde]
+pub struct NextDueResp {
+    pub next_due: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct GovernanceResp {
+    pub config: Option<GovernanceConfig>,
+}
+
+#[cw_serde]
+pub struct MultisigInfo {
+    pub members: Vec<Addr>,
+    pub threshold: u32,
+}
+
+#[cw_serde]
+pub struct MultisigResp {
+    pub multisig: Option<MultisigInfo>,
+}
+
+#[cw_serde]
+pub struct OwnerActionResp {
+    pub id: u64,
+    pub proposer: Addr,
+    pub action: ExecMsg,
+    pub approvals: Vec<Addr>,
+    pub executed: bool,
+}
+
+#[cw_serde]
+pub struct VotingWeightResp {
+    pub weight: Uint128,
+    pub total_weight: Uint128,
+}
+
+#[cw_serde]
+pub struct ProposalResp {
+    pub id: u64,
+    pub proposer: Addr,
+    pub recipient: Addr,
+    pub amount: Vec<Coin>,
+    pub description: String,
+    pub status: ProposalStatus,
+    pub expires: Expiration,
+    pub yes: Uint128,
+    pub no: Uint128,
+    pub total_weight: Uint128,
+}
+
+#[cw_serde]
+pub struct ProposalListResp {
+    pub proposals: Vec<ProposalResp>,
+}
+
+#[cw_serde]
+pub struct VoteInfo {
+    pub voter: Addr,
+    pub yes: bool,
+    pub weight: Uint128,
+}
+
+#[cw_serde]
+pub struct VoteResp {
+    pub vote: Option<VoteInfo>,
+}
+
+#[cw_serde]
+pub struct VoteListResp {
+    pub votes: Vec<VoteInfo>,
+}
+
+#[cw_serde]
+pub struct ReceiptResp {
+    pub receipt: Option<ReceiptConfig>,
 }
 
 #[cw_serde]
@@ -28,11 +380,353 @@ pub struct ValueResp {
     pub value: u64,
 }
 
+#[cw_serde]
+pub struct CheckpointPruningResp {
+    pub pruning: CheckpointPruning,
+}
+
+#[cw_serde]
+pub struct ParentResp {
+    pub parent: Option<Parent>,
+    /// When the parent can next be donated to, for parents with an `interval`.
+    pub next_forward: Option<Expiration>,
+}
+
+/// Link between two contracts in a donation tree, with everything the child contract has
+/// forwarded to its parent so far.
+#[cw_serde]
+pub struct TreeEdge {
+    pub addr: Addr,
+    pub forwarded: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct AncestorsResp {
+    pub ancestors: Vec<TreeEdge>,
+}
+
+#[cw_serde]
+pub struct ChildrenResp {
+    pub children: Vec<TreeEdge>,
+}
+
+#[cw_serde]
+pub struct ParentForwards {
+    pub addr: Addr,
+    pub forwarded: Vec<Coin>,
+    pub count: u64,
+    pub last_height: Option<u64>,
+    /// Transfers to a remote parent which failed or timed out and came back.
+    pub refunded: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct ParentStatsResp {
+    pub parents: Vec<ParentForwards>,
+    pub received_from_children: Vec<Coin>,
+    pub received_from_users: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct DonationResp {
+    pub donor: Addr,
+    pub relayer: Option<Addr>,
+    pub funds: Vec<Coin>,
+    pub memo: Option<String>,
+    pub counted: bool,
+    pub height: u64,
+    pub counter_id: Option<String>,
+}
+
+#[cw_serde]
+pub struct DonorResp {
+    pub donated: Vec<Coin>,
+    pub donations: u64,
+}
+
+#[cw_serde]
+pub struct DonorInfo {
+    pub addr: Addr,
+    pub donated: Vec<Coin>,
+    pub donations: u64,
+}
+
+#[cw_serde]
+pub struct ListDonorsResp {
+    pub donors: Vec<DonorInfo>,
+}
+
+#[cw_serde]
+pub struct AccessModeResp {
+    pub mode: AccessMode,
+}
+
+#[cw_serde]
+pub struct AccessListResp {
+    pub addrs: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct WithdrawableResp {
+    pub amount: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct BeneficiariesResp {
+    pub beneficiaries: Vec<Beneficiary>,
+    pub remainder: Option<String>,
+    pub mode: Option<PayoutMode>,
+}
+
+#[cw_serde]
+pub struct ClaimableResp {
+    pub amount: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct TotalOwedResp {
+    pub amount: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct FeeResp {
+    pub fee: Option<FeeConfig>,
+    /// `None` while the owner of the default counter administers the fee.
+    pub fee_admin: Option<Addr>,
+    pub collected: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct AllowanceResp {
+    pub allowance: Coin,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct AllAllowancesResp {
+    pub allowances: Vec<AllowanceResp>,
+}
+
+#[cw_serde]
+pub struct CounterResp {
+    pub id: String,
+    pub counter: u64,
+    pub minimal_donation: Coin,
+    pub owner: Addr,
+    pub parent: Option<Parent>,
+    /// What the owner can withdraw from this counter right now.
+    pub withdrawable: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct ListCountersResp {
+    pub counters: Vec<CounterResp>,
+}
+
+/// `counter_id` picks a named counter, `None` goes to the default one.
 #[cw_serde]
 pub enum ExecMsg {
-    Donate {},
+    Donate {
+        memo: Option<String>,
+        on_behalf_of: Option<String>,
+        counter_id: Option<String>,
+    },
     Reset {
         counter: u64,
+        counter_id: Option<String>,
+    },
+    /// Sends the funds to `recipient`, or to the sender if not given.
+    Withdraw {
+        amount: Option<Vec<Coin>>,
+        counter_id: Option<String>,
+        recipient: Option<String>,
+    },
+    /// Adds a named counter, owned by `owner` or by the sender. Only the owner of the default
+    /// counter can create them.
+    ///
+    /// A named counter counts its own donations, keeps them in its own balance for its owner
+    /// to withdraw or reset, forwards its part to its own parent and has its values kept for
+    /// `ValueAtHeight`. Donations to it are recorded like any other, and its forwards wait
+    /// for `FlushToParent` like those of the default counter while a flush bounty is set. The
+    /// fee, vesting, beneficiaries, allowances, governance voting weight, receipt tokens,
+    /// subscriptions and IBC syncing only apply to the default counter.
+    CreateCounter {
+        id: String,
+        minimal_donation: Coin,
+        parent: Option<Parent>,
+        owner: Option<String>,
+    },
+    UpdateParent {
+        parent: Option<Parent>,
+    },
+    RegisterChild {},
+    UnregisterChild {},
+    SetAccessMode {
+        mode: AccessMode,
+    },
+    UpdateAccessList {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    IncreaseAllowance {
+        spender: String,
+        amount: Coin,
+        expires: Option<Expiration>,
+    },
+    DecreaseAllowance {
+        spender: String,
+        amount: Coin,
+        expires: Option<Expiration>,
+    },
+    WithdrawAllowance {
+        amount: Coin,
+        recipient: Option<String>,
+    },
+    SetBeneficiaries {
+        beneficiaries: Vec<Beneficiary>,
+        remainder: String,
+        mode: PayoutMode,
+    },
+    RemoveBeneficiaries {},
+    Claim {},
+    UpdateFee {
+        fee: Option<FeeConfig>,
+    },
+    /// Hands the fee over to `admin`, or back to the owner of the default counter with `None`.
+    UpdateFeeAdmin {
+        admin: Option<String>,
+    },
+    SetCheckpointPruning {
+        pruning: CheckpointPruning,
+    },
+    /// Sends the donations counted here since the last sync over `channel`, so the counter on
+    /// the other end counts them too. Contracts built without the `ibc` feature reject it.
+    SyncToRemote {
+        channel: String,
+    },
+    /// Only approved counterparties can open channels. Removing one leaves its open channels
+    /// working.
+    UpdateIbcCounterparties {
+        add: Vec<IbcCounterparty>,
+        remove: Vec<IbcCounterparty>,
+    },
+    UpdateReceipt {
+        receipt: Option<ReceiptConfig>,
+    },
+    /// cw20 receive hook, accepting only the receipt token.
+    Receive(Cw20ReceiveMsg),
+    /// Hands withdrawals from the default counter over to donors for good. The owner can no
+    /// longer withdraw from it, grant allowances on it, redirect it to beneficiaries or change
+    /// its parent, spenders can no longer use their remaining allowances and receipt tokens
+    /// can no longer be redeemed.
+    EnableGovernance {
+        config: GovernanceConfig,
+    },
+    /// Proposes sending `amount` of the default counter funds to `recipient`. The proposer
+    /// votes yes right away.
+    Propose {
+        recipient: String,
+        amount: Vec<Coin>,
+        description: String,
+    },
+    Vote {
+        id: u64,
+        yes: bool,
+    },
+    /// Pays out a passed proposal. Anyone can call it.
+    Execute {
+        id: u64,
+    },
+    /// Hands the default counter over to a new owner. Setting a multisig makes the contract
+    /// itself the owner, running owner actions once enough members approved them.
+    UpdateOwner {
+        owner: Owner,
     },
-    Withdraw {},
-}
\ No newline at end of file
+    /// Proposes an owner action to the multisig members, approving it right away. Only messages
+    /// restricted to the owner are accepted, and withdrawals need an explicit recipient.
+    ProposeOwnerAction {
+        action: Box<ExecMsg>,
+    },
+    /// Approves an owner action, running it once the threshold is met.
+    ApproveOwnerAction {
+        id: u64,
+    },
+    /// Sets up donating `amount` to the default counter every `period` seconds, `periods`
+    /// times, starting now. All of it has to be sent along and is held until donated.
+    Subscribe {
+        amount: Coin,
+        period: u64,
+        periods: u32,
+    },
+    /// Stops a subscription, sending back what wasn't donated yet.
+    CancelSubscription {
+        id: u64,
+    },
+    /// Makes up to `limit` due subscription donations. Anyone can call it.
+    ProcessSubscriptions {
+        limit: Option<u32>,
+    },
+    /// When set, the donation ending a parent donating period of any counter only marks its
+    /// forward as pending, paying `bounty` part of it to whoever flushes it.
+    UpdateFlushBounty {
+        bounty: Option<Decimal>,
+    },
+    /// Forwards the pending part of the funds of counter `counter_id`, or of the default one,
+    /// to its parent. Anyone can call it.
+    FlushToParent {
+        counter_id: Option<String>,
+    },
+    /// Replaces the vesting schedule of the default counter, or drops it with `None`. It
+    /// applies to everything retained so far, not only to later donations.
+    UpdateVesting {
+        vesting: Option<VestingSchedule>,
+    },
+    /// Declares whether the chain runs ibc-hooks with its `ibc_callback` memo support, which
+    /// remote parents need to learn the outcome of their transfers. It can only be turned off
+    /// while no counter has a remote parent.
+    SetIbcHooks {
+        enabled: bool,
+    },
+}
+
+/// Messages sent along with the receipt token through cw20 `Send`.
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Burns the sent receipt tokens, paying out the same share of the withdrawable funds as
+    /// they are of the token supply.
+    Redeem {},
+}
+
+/// Packet exchanged between counting contracts on different chains.
+#[cw_serde]
+pub enum CounterPacket {
+    /// Donations counted on the sending chain, to be added to the receiving counter.
+    Increment { by: u64 },
+}
+
+#[cw_serde]
+pub enum PacketAck {
+    Result(Binary),
+    Error(String),
+}
+
+/// Outcome of ICS-20 transfers to a remote parent, reported by ibc-hooks once they are
+/// acknowledged or time out. Transfers ask for it with an `ibc_callback` memo.
+#[cw_serde]
+pub enum SudoMsg {
+    IbcLifecycleComplete(IbcLifecycleComplete),
+}
+
+#[cw_serde]
+pub enum IbcLifecycleComplete {
+    IbcAck {
+        channel: String,
+        sequence: u64,
+        ack: String,
+        success: bool,
+    },
+    IbcTimeout {
+        channel: String,
+        sequence: u64,
+    },
+}
@@ -1,3 +1,10 @@
 pub mod contract;
+#[cfg(all(test, feature = "factory"))]
+mod factory;
+pub mod fuzz;
+#[cfg(all(test, feature = "ibc"))]
+mod ibc;
 #[cfg(test)]
-mod tests;
\ No newline at end of file
+mod legacy;
+#[cfg(test)]
+mod tests;
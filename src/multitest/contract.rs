@@ -1,11 +1,37 @@
-use cosmwasm_std::{Addr, Coin, StdResult, Empty, StdError};
-use cw_multi_test::{App, Executor, ContractWrapper};
+use cosmwasm_std::{
+    to_json_binary, Addr, Coin, ContractInfoResponse, Decimal, Empty, StdError, StdResult, Uint128,
+    WasmQuery,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+use cw_utils::Expiration;
 
+#[cfg(feature = "factory")]
+use crate::factory::{
+    self,
+    error::FactoryError,
+    msg::{
+        ConfigResp, CountersResp, ExecMsg as FactoryExecMsg,
+        InstantiateMsg as FactoryInstantiateMsg, QueryMsg as FactoryQueryMsg,
+    },
+};
 use crate::{
     error::ContractError,
-    msg::{ExecMsg, InstantiateMsg, QueryMsg, ValueResp, Parent}, execute, instantiate, query, migrate,
+    execute, instantiate, migrate,
+    msg::{
+        AccessListResp, AccessMode, AccessModeResp, AllAllowancesResp, AllowanceResp,
+        AncestorsResp, BeneficiariesResp, Beneficiary, CheckpointPruning, CheckpointPruningResp,
+        ChildrenResp, ClaimableResp, DonationResp, DonorResp, ExecMsg, FeeConfig, FeeResp,
+        FlushBountyResp, GovernanceConfig, InstantiateMsg, InvariantsResp, ListCountersResp,
+        ListDonorsResp, ListSubscriptionsResp, MultisigResp, NextDueResp, Owner, OwnerActionResp,
+        Parent, ParentResp, ParentStatsResp, PayoutMode, ProposalResp, QueryMsg, ReceiptConfig,
+        ReceiptResp, ReceiveMsg, SubscriptionResp, TotalOwedResp, ValueResp, VestingSchedule,
+        VoteListResp, VotingWeightResp, WithdrawableResp,
+    },
+    query,
 };
 
+#[derive(Debug)]
 pub struct CountingContract(Addr);
 
 impl CountingContract {
@@ -18,6 +44,7 @@ impl CountingContract {
         app.store_code(Box::new(contract))
     }
     #[track_caller]
+    #[allow(clippy::too_many_arguments)]
     pub fn instantiate(
         app: &mut App,
         code_id: u64,
@@ -27,15 +54,40 @@ impl CountingContract {
         label: &str,
         admin: Option<&Addr>,
         parent: Option<Parent>
-    ) -> StdResult<CountingContract> {
-        app.instantiate_contract(
+    ) -> Result<CountingContract, ContractError> {
+        Self::instantiate_with_msg(
+            app,
             code_id,
-            sender.clone(),
+            sender,
             &InstantiateMsg {
                 counter,
                 minimal_donation,
                 parent,
+                parent_depth_limit: None,
+                vesting: None,
+                fee: None,
+                fee_admin: None,
+                owner: None,
+                ibc_hooks: false,
             },
+            label,
+            admin,
+        )
+    }
+
+    #[track_caller]
+    pub fn instantiate_with_msg(
+        app: &mut App,
+        code_id: u64,
+        sender: &Addr,
+        msg: &InstantiateMsg,
+        label: &str,
+        admin: Option<&Addr>,
+    ) -> Result<CountingContract, ContractError> {
+        app.instantiate_contract(
+            code_id,
+            sender.clone(),
+            msg,
             &[],
             label,
             admin.map(Addr::to_string),
@@ -50,25 +102,185 @@ impl CountingContract {
         app: &mut App,
         sender: &Addr,
         funds: &[Coin],
+    ) -> Result<AppResponse, ContractError> {
+        self.donate_with(app, sender, funds, None, None)
+    }
+
+    #[track_caller]
+    pub fn donate_with(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        funds: &[Coin],
+        memo: Option<&str>,
+        on_behalf_of: Option<&Addr>,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Donate {
+                memo: memo.map(str::to_owned),
+                on_behalf_of: on_behalf_of.map(Addr::to_string),
+                counter_id: None,
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn donate_to(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        funds: &[Coin],
+        counter_id: &str,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Donate {
+                memo: None,
+                on_behalf_of: None,
+                counter_id: Some(counter_id.to_owned()),
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn withdraw(&self, app: &mut App, sender: &Addr) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Withdraw {
+                amount: None,
+                counter_id: None,
+                recipient: None,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn withdraw_amount(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        amount: &[Coin],
     ) -> Result<(), ContractError> {
-        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Donate {}, funds)
-            .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Withdraw {
+                amount: Some(amount.to_vec()),
+                counter_id: None,
+                recipient: None,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
         Ok(())
     }
 
     #[track_caller]
-    pub fn withdraw(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
-        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Withdraw {}, &[])
-            .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+    pub fn withdraw_counter(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        counter_id: &str,
+        amount: Option<&[Coin]>,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Withdraw {
+                amount: amount.map(<[Coin]>::to_vec),
+                counter_id: Some(counter_id.to_owned()),
+                recipient: None,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn reset(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        counter: u64,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Reset {
+                counter,
+                counter_id: None,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn reset_counter(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        counter_id: &str,
+        counter: u64,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Reset {
+                counter,
+                counter_id: Some(counter_id.to_owned()),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn create_counter(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: &str,
+        minimal_donation: Coin,
+        parent: Option<Parent>,
+        owner: Option<&Addr>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::CreateCounter {
+                id: id.to_owned(),
+                minimal_donation,
+                parent,
+                owner: owner.map(Addr::to_string),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
         Ok(())
     }
 
     #[track_caller]
-    pub fn reset(&self, app: &mut App, sender: &Addr, counter: u64) -> Result<(), ContractError> {
+    pub fn update_parent(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        parent: Option<Parent>,
+    ) -> Result<(), ContractError> {
         app.execute_contract(
             sender.clone(),
             self.0.clone(),
-            &ExecMsg::Reset { counter: counter },
+            &ExecMsg::UpdateParent { parent },
             &[],
         )
         .map_err(|err| err.downcast::<ContractError>().unwrap())?;
@@ -76,16 +288,1000 @@ impl CountingContract {
     }
 
     #[track_caller]
-    pub fn query_value(&self, app: &App) -> StdResult<ValueResp> {
-        app.wrap()
-            .query_wasm_smart(self.0.clone(), &QueryMsg::Value {})
+    pub fn set_access_mode(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        mode: AccessMode,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::SetAccessMode { mode },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
     }
 
     #[track_caller]
-    pub fn migrate(app: &mut App, contract: Addr, code_id: u64, sender: &Addr) -> StdResult<Self> {
-        app.migrate_contract(sender.clone(), contract.clone(), &Empty {}, code_id)
-            .map_err(|err| err.downcast::<StdError>().unwrap())?;
+    pub fn update_access_list(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        add: &[&Addr],
+        remove: &[&Addr],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateAccessList {
+                add: add.iter().map(|addr| addr.to_string()).collect(),
+                remove: remove.iter().map(|addr| addr.to_string()).collect(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
 
-        Ok(CountingContract(contract.clone()))
+    #[track_caller]
+    pub fn increase_allowance(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        spender: &Addr,
+        amount: Coin,
+        expires: Option<Expiration>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::IncreaseAllowance {
+                spender: spender.to_string(),
+                amount,
+                expires,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn decrease_allowance(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        spender: &Addr,
+        amount: Coin,
+        expires: Option<Expiration>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::DecreaseAllowance {
+                spender: spender.to_string(),
+                amount,
+                expires,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn withdraw_allowance(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        amount: Coin,
+        recipient: Option<&Addr>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::WithdrawAllowance {
+                amount,
+                recipient: recipient.map(Addr::to_string),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn set_beneficiaries(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        beneficiaries: &[(&Addr, Decimal)],
+        remainder: &Addr,
+        mode: PayoutMode,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::SetBeneficiaries {
+                beneficiaries: beneficiaries
+                    .iter()
+                    .map(|(addr, share)| Beneficiary {
+                        addr: addr.to_string(),
+                        share: *share,
+                    })
+                    .collect(),
+                remainder: remainder.to_string(),
+                mode,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn claim(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Claim {}, &[])
+            .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn update_fee(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        fee: Option<FeeConfig>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateFee { fee },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn update_receipt(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        receipt: Option<ReceiptConfig>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateReceipt { receipt },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
     }
+
+    /// Sends `amount` of the receipt `token` to the contract to redeem it.
+    #[track_caller]
+    pub fn redeem(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        token: &Addr,
+        amount: u128,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.0.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(&ReceiveMsg::Redeem {}).unwrap(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn enable_governance(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        config: GovernanceConfig,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::EnableGovernance { config },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn propose(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        recipient: &Addr,
+        amount: &[Coin],
+        description: &str,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Propose {
+                recipient: recipient.to_string(),
+                amount: amount.to_vec(),
+                description: description.to_owned(),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn vote(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+        yes: bool,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Vote { id, yes },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn execute_proposal(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Execute { id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn update_owner(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        owner: Owner,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateOwner { owner },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn propose_owner_action(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        action: ExecMsg,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ProposeOwnerAction {
+                action: Box::new(action),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn approve_owner_action(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ApproveOwnerAction { id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn subscribe(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        amount: Coin,
+        period: u64,
+        periods: u32,
+        funds: &[Coin],
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Subscribe {
+                amount,
+                period,
+                periods,
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn cancel_subscription(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        id: u64,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::CancelSubscription { id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn process_subscriptions(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        limit: Option<u32>,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ProcessSubscriptions { limit },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn update_flush_bounty(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        bounty: Option<Decimal>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateFlushBounty { bounty },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn update_vesting(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        vesting: Option<VestingSchedule>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::UpdateVesting { vesting },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn flush_to_parent(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::FlushToParent { counter_id: None },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn flush_counter_to_parent(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        counter_id: &str,
+    ) -> Result<AppResponse, ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::FlushToParent {
+                counter_id: Some(counter_id.to_owned()),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn set_checkpoint_pruning(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        pruning: CheckpointPruning,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::SetCheckpointPruning { pruning },
+            &[],
+        )
+        .map_err(|err| err.downcast::<ContractError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn query_value(&self, app: &App) -> StdResult<ValueResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Value {})
+    }
+
+    #[track_caller]
+    pub fn query_value_at_height(&self, app: &App, height: u64) -> StdResult<ValueResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::ValueAtHeight {
+                height,
+                counter_id: None,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_counter_value_at_height(
+        &self,
+        app: &App,
+        counter_id: &str,
+        height: u64,
+    ) -> StdResult<ValueResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::ValueAtHeight {
+                height,
+                counter_id: Some(counter_id.to_owned()),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_checkpoint_pruning(&self, app: &App) -> StdResult<CheckpointPruningResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::CheckpointPruning {})
+    }
+
+    #[track_caller]
+    pub fn query_list_counters(
+        &self,
+        app: &App,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<ListCountersResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::ListCounters {
+                start_after: start_after.map(str::to_owned),
+                limit,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_parent(&self, app: &App) -> StdResult<ParentResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Parent {})
+    }
+
+    #[track_caller]
+    pub fn query_ancestors(&self, app: &App, limit: Option<u32>) -> StdResult<AncestorsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Ancestors { limit })
+    }
+
+    #[track_caller]
+    pub fn query_children(&self, app: &App) -> StdResult<ChildrenResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Children {})
+    }
+
+    #[track_caller]
+    pub fn query_parent_stats(&self, app: &App) -> StdResult<ParentStatsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::ParentStats {})
+    }
+
+    #[track_caller]
+    pub fn query_donation(&self, app: &App, id: u64) -> StdResult<DonationResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Donation { id })
+    }
+
+    #[track_caller]
+    pub fn query_donor(&self, app: &App, addr: &Addr) -> StdResult<DonorResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Donor {
+                addr: addr.to_string(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_list_donors(
+        &self,
+        app: &App,
+        start_after: Option<&Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<ListDonorsResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::ListDonors {
+                start_after: start_after.map(Addr::to_string),
+                limit,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_access_mode(&self, app: &App) -> StdResult<AccessModeResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::AccessMode {})
+    }
+
+    #[track_caller]
+    pub fn query_access_list(
+        &self,
+        app: &App,
+        start_after: Option<&Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<AccessListResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::AccessList {
+                start_after: start_after.map(Addr::to_string),
+                limit,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_withdrawable(&self, app: &App) -> StdResult<WithdrawableResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Withdrawable {})
+    }
+
+    #[track_caller]
+    pub fn query_beneficiaries(&self, app: &App) -> StdResult<BeneficiariesResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Beneficiaries {})
+    }
+
+    #[track_caller]
+    pub fn query_claimable(&self, app: &App, addr: &Addr) -> StdResult<ClaimableResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Claimable {
+                addr: addr.to_string(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_total_owed(&self, app: &App) -> StdResult<TotalOwedResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::TotalOwed {})
+    }
+
+    #[track_caller]
+    pub fn query_fee(&self, app: &App) -> StdResult<FeeResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Fee {})
+    }
+
+    #[track_caller]
+    pub fn query_receipt(&self, app: &App) -> StdResult<ReceiptResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Receipt {})
+    }
+
+    #[track_caller]
+    pub fn query_subscription(&self, app: &App, id: u64) -> StdResult<SubscriptionResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Subscription { id })
+    }
+
+    #[track_caller]
+    pub fn query_list_subscriptions(
+        &self,
+        app: &App,
+        donor: Option<&Addr>,
+    ) -> StdResult<ListSubscriptionsResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::ListSubscriptions {
+                donor: donor.map(Addr::to_string),
+                start_after: None,
+                limit: None,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_next_due(&self, app: &App) -> StdResult<NextDueResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::NextDue {})
+    }
+
+    #[track_caller]
+    pub fn query_flush_bounty(&self, app: &App) -> StdResult<FlushBountyResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::FlushBounty { counter_id: None })
+    }
+
+    #[track_caller]
+    pub fn query_counter_flush_bounty(
+        &self,
+        app: &App,
+        counter_id: &str,
+    ) -> StdResult<FlushBountyResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::FlushBounty {
+                counter_id: Some(counter_id.to_owned()),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_invariants(&self, app: &App) -> StdResult<InvariantsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Invariants {})
+    }
+
+    /// Panics listing every accounting violation, meant to run after each step of a test. Also
+    /// checks the donor totals add up to what the contract received, which is too expensive for
+    /// the contract to do in a single query.
+    #[track_caller]
+    pub fn assert_invariants(&self, app: &App) {
+        let InvariantsResp {
+            mut violations,
+            received,
+        } = self.query_invariants(app).unwrap();
+
+        let mut donated: Vec<Coin> = vec![];
+        let mut start_after = None;
+        loop {
+            let donors = self
+                .query_list_donors(app, start_after.as_ref(), None)
+                .unwrap()
+                .donors;
+            match donors.last() {
+                Some(last) => start_after = Some(last.addr.clone()),
+                None => break,
+            }
+            for coin in donors.iter().flat_map(|donor| &donor.donated) {
+                match donated.iter_mut().find(|total| total.denom == coin.denom) {
+                    Some(total) => total.amount += coin.amount,
+                    None => donated.push(coin.clone()),
+                }
+            }
+        }
+        donated.retain(|coin| !coin.amount.is_zero());
+        donated.sort_by(|a, b| a.denom.cmp(&b.denom));
+        if donated != received {
+            violations.push(format!(
+                "donor totals {:?} differ from the received {:?}",
+                donated, received
+            ));
+        }
+
+        assert!(
+            violations.is_empty(),
+            "invariants violated:\n{}",
+            violations.join("\n")
+        );
+    }
+
+    #[track_caller]
+    pub fn query_multisig(&self, app: &App) -> StdResult<MultisigResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Multisig {})
+    }
+
+    #[track_caller]
+    pub fn query_owner_action(&self, app: &App, id: u64) -> StdResult<OwnerActionResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::OwnerAction { id })
+    }
+
+    #[track_caller]
+    pub fn query_voting_weight(&self, app: &App, addr: &Addr) -> StdResult<VotingWeightResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::VotingWeight {
+                addr: addr.to_string(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_proposal(&self, app: &App, id: u64) -> StdResult<ProposalResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Proposal { id })
+    }
+
+    #[track_caller]
+    pub fn query_list_votes(
+        &self,
+        app: &App,
+        id: u64,
+        start_after: Option<&Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<VoteListResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::ListVotes {
+                id,
+                start_after: start_after.map(Addr::to_string),
+                limit,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_allowance(
+        &self,
+        app: &App,
+        spender: &Addr,
+        denom: &str,
+    ) -> StdResult<AllowanceResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::Allowance {
+                spender: spender.to_string(),
+                denom: denom.to_owned(),
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_all_allowances(
+        &self,
+        app: &App,
+        spender: &Addr,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<AllAllowancesResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::AllAllowances {
+                spender: spender.to_string(),
+                start_after: start_after.map(str::to_owned),
+                limit,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn migrate(app: &mut App, contract: Addr, code_id: u64, sender: &Addr) -> StdResult<Self> {
+        app.migrate_contract(sender.clone(), contract.clone(), &Empty {}, code_id)
+            .map_err(|err| err.downcast::<StdError>().unwrap())?;
+
+        Ok(CountingContract(contract.clone()))
+    }
+}
+
+#[cfg(feature = "factory")]
+pub struct FactoryContract(Addr);
+
+#[cfg(feature = "factory")]
+impl FactoryContract {
+    pub fn addr(&self) -> &Addr {
+        &self.0
+    }
+
+    pub fn store_code(app: &mut App) -> u64 {
+        let contract = ContractWrapper::new(factory::execute, factory::instantiate, factory::query)
+            .with_reply(factory::reply)
+            .with_migrate(factory::migrate);
+        app.store_code(Box::new(contract))
+    }
+
+    #[track_caller]
+    pub fn instantiate(
+        app: &mut App,
+        code_id: u64,
+        sender: &Addr,
+        counter_code_id: u64,
+        label: &str,
+        admin: Option<&Addr>,
+    ) -> StdResult<Self> {
+        app.instantiate_contract(
+            code_id,
+            sender.clone(),
+            &FactoryInstantiateMsg { counter_code_id },
+            &[],
+            label,
+            admin.map(Addr::to_string),
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(FactoryContract)
+    }
+
+    #[track_caller]
+    pub fn create_counter(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        msg: InstantiateMsg,
+        label: &str,
+        funds: &[Coin],
+    ) -> Result<CountingContract, FactoryError> {
+        let msg = FactoryExecMsg::CreateCounter {
+            msg,
+            label: label.to_owned(),
+        };
+        self.create(app, sender, &msg, funds)
+    }
+
+    /// Sends a create message to the factory and returns the counter, as found in the factory
+    /// reply.
+    #[track_caller]
+    fn create(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        msg: &FactoryExecMsg,
+        funds: &[Coin],
+    ) -> Result<CountingContract, FactoryError> {
+        let resp = app
+            .execute_contract(sender.clone(), self.0.clone(), msg, funds)
+            .map_err(|err| err.downcast::<FactoryError>().unwrap())?;
+
+        let addr = resp
+            .events
+            .iter()
+            .filter(|event| event.ty == "wasm")
+            .filter(|event| {
+                event
+                    .attributes
+                    .iter()
+                    .any(|attr| attr.key == "_contract_addr" && attr.value == self.0.as_str())
+            })
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "counter")
+            .map(|attr| Addr::unchecked(&attr.value))
+            .unwrap();
+
+        Ok(CountingContract(addr))
+    }
+
+    #[track_caller]
+    pub fn update_code_id(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        code_id: u64,
+    ) -> Result<(), FactoryError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &FactoryExecMsg::UpdateCodeId { code_id },
+            &[],
+        )
+        .map_err(|err| err.downcast::<FactoryError>().unwrap())?;
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn migrate_counters(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        start_after: Option<&Addr>,
+        limit: Option<u32>,
+    ) -> Result<AppResponse, FactoryError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &FactoryExecMsg::MigrateCounters {
+                start_after: start_after.map(Addr::to_string),
+                limit,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast::<FactoryError>().unwrap())
+    }
+
+    #[track_caller]
+    pub fn query_config(&self, app: &App) -> StdResult<ConfigResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &FactoryQueryMsg::Config {})
+    }
+
+    #[track_caller]
+    pub fn query_counters_by_creator(
+        &self,
+        app: &App,
+        creator: &Addr,
+        start_after: Option<&Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<CountersResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &FactoryQueryMsg::CountersByCreator {
+                creator: creator.to_string(),
+                start_after: start_after.map(Addr::to_string),
+                limit,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_all_counters(
+        &self,
+        app: &App,
+        start_after: Option<&Addr>,
+        limit: Option<u32>,
+    ) -> StdResult<CountersResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &FactoryQueryMsg::AllCounters {
+                start_after: start_after.map(Addr::to_string),
+                limit,
+            },
+        )
+    }
+}
+
+/// Code id the contract at `addr` currently runs.
+#[track_caller]
+pub fn query_code_id(app: &App, addr: &Addr) -> StdResult<u64> {
+    let info: ContractInfoResponse = app.wrap().query(
+        &WasmQuery::ContractInfo {
+            contract_addr: addr.to_string(),
+        }
+        .into(),
+    )?;
+    Ok(info.code_id)
 }
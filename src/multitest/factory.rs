@@ -0,0 +1,437 @@
+//! cw-multi-test 0.15 supports neither `Instantiate2` nor code info queries, so these tests call
+//! the factory entry points directly, with code info answered by a mocked querier and the
+//! instantiation completed through `reply`.
+
+use std::marker::PhantomData;
+
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    coin, from_json, to_json_binary, Addr, Api, Binary, CanonicalAddr, CodeInfoResponse,
+    ContractResult, CosmosMsg, Decimal, Env, HexBinary, OwnedDeps, RecoverPubkeyError, Reply,
+    StdResult, SubMsgResponse, SubMsgResult, SystemError, SystemResult, VerificationError, WasmMsg,
+    WasmQuery,
+};
+use cw2::ContractVersion;
+
+use crate::factory::contract::{predict_address as factory_address, INSTANTIATE_COUNTER_REPLY_ID};
+use crate::factory::msg::{
+    CounterEntry, CountersResp, ExecMsg, InstantiateMsg, PredictAddressResp, QueryMsg,
+};
+use crate::factory::{execute, instantiate, query, reply};
+use crate::msg::{ChildrenResp, ExecMsg as CounterExecMsg, Parent, QueryMsg as CounterQueryMsg};
+
+const ATOM: &str = "atom";
+const COUNTER_CODE_ID: u64 = 3;
+const CHECKSUM: [u8; 32] = [0x13; 32];
+/// Addresses are hex encoded canonical addresses, see `HexApi`.
+const FACTORY: &str = "facade0000000000000000000000000000000001";
+const OWNER: &str = "0000000000000000000000000000000000000a11";
+const CREATOR: &str = "c0ffee0000000000000000000000000000000002";
+
+/// `MockApi` only humanizes canonical addresses of its own length, while `instantiate2_address`
+/// returns 32 bytes. This one takes any length, using the hex encoding as the human address.
+#[derive(Default)]
+struct HexApi(MockApi);
+
+impl Api for HexApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.addr_humanize(&self.addr_canonicalize(human)?)
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        Ok(HexBinary::from_hex(human)?.to_vec().into())
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        Ok(Addr::unchecked(
+            HexBinary::from(canonical.as_slice()).to_hex(),
+        ))
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.0
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.0
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.0.debug(message)
+    }
+}
+
+type HexDeps = OwnedDeps<MockStorage, HexApi, MockQuerier>;
+
+fn hex_deps(querier: MockQuerier) -> HexDeps {
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: HexApi::default(),
+        querier,
+        custom_query_type: PhantomData,
+    }
+}
+
+fn env_at(addr: &str) -> Env {
+    let mut env = mock_env();
+    env.contract.address = Addr::unchecked(addr);
+    env
+}
+
+fn factory_env() -> Env {
+    env_at(FACTORY)
+}
+
+fn unsupported() -> SystemResult<ContractResult<Binary>> {
+    SystemResult::Err(SystemError::UnsupportedRequest {
+        kind: "wasm".to_owned(),
+    })
+}
+
+/// Querier answering code info of any code with `checksum`.
+fn code_info_querier(checksum: HexBinary) -> MockQuerier {
+    let mut querier = MockQuerier::new(&[]);
+    querier.update_wasm(move |query| match query {
+        WasmQuery::CodeInfo { code_id } => {
+            let info = CodeInfoResponse::new(*code_id, OWNER.to_owned(), checksum.clone());
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&info).unwrap()))
+        }
+        _ => unsupported(),
+    });
+    querier
+}
+
+fn instantiated() -> HexDeps {
+    let mut deps = hex_deps(code_info_querier(HexBinary::from(CHECKSUM)));
+    instantiate(
+        deps.as_mut(),
+        factory_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            counter_code_id: COUNTER_CODE_ID,
+        },
+    )
+    .unwrap();
+    deps
+}
+
+fn predict_address(deps: &HexDeps, label: &str) -> PredictAddressResp {
+    let msg = QueryMsg::PredictAddress {
+        creator: CREATOR.to_owned(),
+        label: label.to_owned(),
+    };
+    from_json(query(deps.as_ref(), factory_env(), msg).unwrap()).unwrap()
+}
+
+/// `MsgInstantiateContractResponse` with just the address, as the chain sets it in reply data.
+fn instantiate_response(addr: &str) -> Binary {
+    let mut data = vec![0x0a, addr.len() as u8];
+    data.extend_from_slice(addr.as_bytes());
+    Binary::from(data)
+}
+
+fn counter_msg(parent: Option<Parent>) -> crate::msg::InstantiateMsg {
+    crate::msg::InstantiateMsg {
+        counter: 0,
+        minimal_donation: coin(0, ATOM),
+        parent,
+        parent_depth_limit: None,
+        vesting: None,
+        fee: None,
+        fee_admin: None,
+        owner: None,
+        ibc_hooks: false,
+    }
+}
+
+/// Creates a counter through `CreateCounter2` and completes it with the reply the chain would
+/// send. Returns the counter address and the message it is instantiated with.
+fn create_counter2(
+    deps: &mut HexDeps,
+    label: &str,
+    msg: crate::msg::InstantiateMsg,
+) -> (Addr, crate::msg::InstantiateMsg) {
+    let predicted = predict_address(deps, label);
+    let resp = execute(
+        deps.as_mut(),
+        factory_env(),
+        mock_info(CREATOR, &[]),
+        ExecMsg::CreateCounter2 {
+            msg,
+            label: label.to_owned(),
+        },
+    )
+    .unwrap();
+
+    let msg = match &resp.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate2 { msg, salt, .. }) => {
+            assert_eq!(*salt, predicted.salt);
+            from_json(msg).unwrap()
+        }
+        msg => panic!("unexpected message: {msg:?}"),
+    };
+
+    reply(
+        deps.as_mut(),
+        factory_env(),
+        Reply {
+            id: INSTANTIATE_COUNTER_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(instantiate_response(predicted.addr.as_str())),
+            }),
+        },
+    )
+    .unwrap();
+
+    (predicted.addr, msg)
+}
+
+/// wasmd's `Instantiate2` test vector without a message, so predicted addresses match the ones
+/// the chain assigns.
+#[test]
+fn instantiate2_test_vector() {
+    let checksum =
+        HexBinary::from_hex("13a1fc994cc6d1c81b746ee0c0ff6f90043875e0bf1d9be6b7d779fc978dc2a5")
+            .unwrap();
+    let deps = hex_deps(code_info_querier(checksum));
+    let env = env_at("9999999999aaaaaaaaaabbbbbbbbbbcccccccccc");
+
+    let addr = factory_address(deps.as_ref(), &env, COUNTER_CODE_ID, b"a").unwrap();
+    assert_eq!(
+        addr,
+        "5e865d3e45ad3e961f77fd77d46543417ced44d924dc3e079b5415ff6775f847"
+    );
+}
+
+/// A child created with its parent's predicted address registers at the parent once both are
+/// instantiated. The counters run on their own mocked storage, answering each other's queries.
+#[test]
+fn parent_child_registration() {
+    let mut deps = instantiated();
+
+    let (parent, parent_msg) = create_counter2(&mut deps, "Parent", counter_msg(None));
+    let (child, child_msg) = create_counter2(
+        &mut deps,
+        "Child",
+        counter_msg(Some(Parent {
+            addr: parent.to_string(),
+            donating_period: 1,
+            part: Decimal::percent(10),
+            remote: None,
+            interval: None,
+        })),
+    );
+    assert_eq!(child_msg.parent.as_ref().unwrap().addr, parent.as_str());
+
+    let parent_version = to_json_binary(&ContractVersion {
+        contract: env!("CARGO_PKG_NAME").to_owned(),
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+    })
+    .unwrap();
+    let parent_addr = parent.to_string();
+    let mut querier = MockQuerier::new(&[]);
+    querier.update_wasm(move |query| match query {
+        WasmQuery::Raw { contract_addr, key }
+            if *contract_addr == parent_addr && key.as_slice() == b"contract_info" =>
+        {
+            SystemResult::Ok(ContractResult::Ok(parent_version.clone()))
+        }
+        WasmQuery::Smart { contract_addr, .. } if *contract_addr == parent_addr => {
+            let resp = crate::msg::ParentResp {
+                parent: None,
+                next_forward: None,
+            };
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&resp).unwrap()))
+        }
+        _ => unsupported(),
+    });
+    let mut child_deps = hex_deps(querier);
+    let resp = crate::instantiate(
+        child_deps.as_mut(),
+        env_at(child.as_str()),
+        mock_info(FACTORY, &[]),
+        child_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        resp.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: parent.to_string(),
+            msg: to_json_binary(&CounterExecMsg::RegisterChild {}).unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let child_parent = crate::query(
+        child_deps.as_ref(),
+        env_at(child.as_str()),
+        CounterQueryMsg::Parent {},
+    )
+    .unwrap();
+    let child_addr = child.to_string();
+    let mut querier = MockQuerier::new(&[]);
+    querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, msg } if *contract_addr == child_addr => {
+            assert!(matches!(from_json(msg), Ok(CounterQueryMsg::Parent {})));
+            SystemResult::Ok(ContractResult::Ok(child_parent.clone()))
+        }
+        _ => unsupported(),
+    });
+    let mut parent_deps = hex_deps(querier);
+    crate::instantiate(
+        parent_deps.as_mut(),
+        env_at(parent.as_str()),
+        mock_info(FACTORY, &[]),
+        parent_msg,
+    )
+    .unwrap();
+    crate::execute(
+        parent_deps.as_mut(),
+        env_at(parent.as_str()),
+        mock_info(child.as_str(), &[]),
+        CounterExecMsg::RegisterChild {},
+    )
+    .unwrap();
+
+    let children: ChildrenResp = from_json(
+        crate::query(
+            parent_deps.as_ref(),
+            env_at(parent.as_str()),
+            CounterQueryMsg::Children {},
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(children.children.len(), 1);
+    assert_eq!(children.children[0].addr, child);
+
+    let counters: CountersResp = from_json(
+        query(
+            deps.as_ref(),
+            factory_env(),
+            QueryMsg::AllCounters {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(counters.counters.len(), 2);
+}
+
+#[test]
+fn predictable_addresses() {
+    let mut deps = instantiated();
+
+    let parent = predict_address(&deps, "Parent");
+    let child = predict_address(&deps, "Child");
+    assert_ne!(parent.salt, child.salt);
+    assert_ne!(parent.addr, child.addr);
+    assert_eq!(predict_address(&deps, "Parent"), parent);
+
+    let msg = crate::msg::InstantiateMsg {
+        counter: 0,
+        minimal_donation: coin(0, ATOM),
+        parent: None,
+        parent_depth_limit: None,
+        vesting: None,
+        fee: None,
+        fee_admin: None,
+        owner: None,
+        ibc_hooks: false,
+    };
+    let resp = execute(
+        deps.as_mut(),
+        factory_env(),
+        mock_info(CREATOR, &[]),
+        ExecMsg::CreateCounter2 {
+            msg,
+            label: "Parent".to_owned(),
+        },
+    )
+    .unwrap();
+
+    let predicted = resp
+        .attributes
+        .iter()
+        .find(|attr| attr.key == "predicted_address")
+        .unwrap();
+    assert_eq!(predicted.value, parent.addr.as_str());
+
+    assert_eq!(resp.messages.len(), 1);
+    assert_eq!(resp.messages[0].id, INSTANTIATE_COUNTER_REPLY_ID);
+    match &resp.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate2 {
+            admin,
+            code_id,
+            label,
+            salt,
+            ..
+        }) => {
+            assert_eq!(admin.as_deref(), Some(FACTORY));
+            assert_eq!(*code_id, COUNTER_CODE_ID);
+            assert_eq!(label, "Parent");
+            assert_eq!(*salt, parent.salt);
+        }
+        msg => panic!("unexpected message: {msg:?}"),
+    }
+
+    reply(
+        deps.as_mut(),
+        factory_env(),
+        Reply {
+            id: INSTANTIATE_COUNTER_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(instantiate_response(parent.addr.as_str())),
+            }),
+        },
+    )
+    .unwrap();
+
+    let msg = QueryMsg::AllCounters {
+        start_after: None,
+        limit: None,
+    };
+    let counters: CountersResp =
+        from_json(query(deps.as_ref(), factory_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        counters.counters,
+        vec![CounterEntry {
+            addr: parent.addr,
+            creator: Addr::unchecked(CREATOR),
+        }]
+    );
+}
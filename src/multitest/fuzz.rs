@@ -0,0 +1,355 @@
+//! Property-based harness running random sequences of donations, resets, withdrawals and
+//! migrations against `App` and checking them against a pure Rust model of the contract.
+//! Failing sequences are shrunk by proptest to the smallest one still failing.
+
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Uint128};
+use cw_multi_test::{App, Executor};
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+use proptest::test_runner::TestCaseError;
+
+use super::contract::CountingContract;
+use crate::msg::{ExecMsg, Parent};
+
+/// Denoms donated in generated actions, in the order the bank keeps them.
+pub const DENOMS: [&str; 3] = ["atom", "juno", "osmo"];
+/// Addresses sending generated actions, the first one owns the tested contract.
+pub const SENDERS: [&str; 4] = ["owner", "alice", "bob", "carol"];
+/// What every sender holds of each denom at the start.
+pub const INITIAL_BALANCE: u128 = 1_000_000;
+
+/// Parent the tested contract forwards to, a fresh contract counting every donation.
+#[derive(Clone, Debug)]
+pub struct ParentSetup {
+    pub period: u64,
+    pub part: Decimal,
+}
+
+#[derive(Clone, Debug)]
+pub struct Setup {
+    pub counter: u64,
+    pub minimal_donation: Coin,
+    pub parent: Option<ParentSetup>,
+}
+
+/// Single step of a generated sequence. `sender` indexes `SENDERS`.
+#[derive(Clone, Debug)]
+pub enum Action {
+    Donate {
+        sender: usize,
+        funds: Vec<Coin>,
+    },
+    Reset {
+        sender: usize,
+        counter: u64,
+    },
+    Withdraw {
+        sender: usize,
+    },
+    /// Migrates the contract to its own code, by its admin.
+    Migrate,
+}
+
+pub fn setup() -> impl Strategy<Value = Setup> {
+    let parent = (1..=4u64, 0..=100u64).prop_map(|(period, percent)| ParentSetup {
+        period,
+        part: Decimal::percent(percent),
+    });
+    (
+        0..100u64,
+        0..DENOMS.len(),
+        0..=20u128,
+        proptest::option::of(parent),
+    )
+        .prop_map(|(counter, denom, amount, parent)| Setup {
+            counter,
+            minimal_donation: Coin::new(amount, DENOMS[denom]),
+            parent,
+        })
+}
+
+pub fn action() -> impl Strategy<Value = Action> {
+    let funds = btree_map(0..DENOMS.len(), 1..=50u128, 0..=DENOMS.len()).prop_map(|funds| {
+        funds
+            .into_iter()
+            .map(|(denom, amount)| Coin::new(amount, DENOMS[denom]))
+            .collect::<Vec<_>>()
+    });
+    prop_oneof![
+        4 => (0..SENDERS.len(), funds).prop_map(|(sender, funds)| Action::Donate { sender, funds }),
+        1 => (0..SENDERS.len(), 0..100u64)
+            .prop_map(|(sender, counter)| Action::Reset { sender, counter }),
+        1 => (0..SENDERS.len()).prop_map(|sender| Action::Withdraw { sender }),
+        1 => Just(Action::Migrate),
+    ]
+}
+
+pub fn actions(max_len: usize) -> impl Strategy<Value = Vec<Action>> {
+    vec(action(), 1..=max_len)
+}
+
+type Balance = BTreeMap<String, u128>;
+
+fn add(balance: &mut Balance, funds: &[Coin]) {
+    for coin in funds {
+        *balance.entry(coin.denom.clone()).or_default() += coin.amount.u128();
+    }
+    balance.retain(|_, amount| *amount > 0);
+}
+
+fn sub(balance: &mut Balance, funds: &[Coin]) {
+    for coin in funds {
+        *balance.get_mut(&coin.denom).unwrap() -= coin.amount.u128();
+    }
+    balance.retain(|_, amount| *amount > 0);
+}
+
+fn to_balance(coins: Vec<Coin>) -> Balance {
+    let mut balance = Balance::new();
+    add(&mut balance, &coins);
+    balance
+}
+
+/// What the tested contract, its parent and the senders should hold after each action.
+#[derive(Clone, Debug)]
+pub struct Model {
+    pub counter: u64,
+    pub minimal_donation: Coin,
+    pub parent: Option<ParentSetup>,
+    pub donating_parent: Option<u64>,
+    pub balance: Balance,
+    pub parent_counter: u64,
+    pub parent_balance: Balance,
+    pub wallets: Vec<Balance>,
+}
+
+impl Model {
+    pub fn new(setup: &Setup) -> Self {
+        let wallet: Balance = DENOMS
+            .iter()
+            .map(|denom| (denom.to_string(), INITIAL_BALANCE))
+            .collect();
+        Self {
+            counter: setup.counter,
+            minimal_donation: setup.minimal_donation.clone(),
+            parent: setup.parent.clone(),
+            donating_parent: setup.parent.as_ref().map(|parent| parent.period),
+            balance: Balance::new(),
+            parent_counter: 0,
+            parent_balance: Balance::new(),
+            wallets: vec![wallet; SENDERS.len()],
+        }
+    }
+
+    /// Applies `action`, returning whether the contract should accept it.
+    pub fn apply(&mut self, action: &Action) -> bool {
+        match action {
+            // Donations without funds are accepted too, they are only counted when no minimal
+            // amount is required
+            Action::Donate { sender, funds } => {
+                sub(&mut self.wallets[*sender], funds);
+                add(&mut self.balance, funds);
+
+                let counted = self.minimal_donation.amount.is_zero()
+                    || funds.iter().any(|coin| {
+                        coin.denom == self.minimal_donation.denom
+                            && coin.amount >= self.minimal_donation.amount
+                    });
+                if !counted {
+                    return true;
+                }
+                self.counter += 1;
+
+                if let (Some(countdown), Some(parent)) = (&mut self.donating_parent, &self.parent) {
+                    *countdown -= 1;
+                    if *countdown == 0 {
+                        *countdown = parent.period;
+                        let share: Vec<_> = self
+                            .balance
+                            .iter()
+                            .map(|(denom, amount)| {
+                                Coin::new((Uint128::new(*amount) * parent.part).u128(), denom)
+                            })
+                            .collect();
+                        sub(&mut self.balance, &share);
+                        add(&mut self.parent_balance, &share);
+                        self.parent_counter += 1;
+                    }
+                }
+                true
+            }
+            Action::Reset { sender, counter } => {
+                if *sender != 0 {
+                    return false;
+                }
+                self.counter = *counter;
+                true
+            }
+            Action::Withdraw { sender } => {
+                if *sender != 0 {
+                    return false;
+                }
+                let balance = std::mem::take(&mut self.balance);
+                for (denom, amount) in balance {
+                    add(&mut self.wallets[0], &[Coin::new(amount, denom)]);
+                }
+                true
+            }
+            Action::Migrate => true,
+        }
+    }
+}
+
+/// Sends `msg` with `funds` to `contract`, telling whether it was accepted. Unlike the
+/// `CountingContract` helpers it does not expect every failure to be a `ContractError`, so bank
+/// errors are rejections rather than panics.
+fn execute(
+    app: &mut App,
+    sender: &Addr,
+    contract: &CountingContract,
+    msg: &ExecMsg,
+    funds: &[Coin],
+) -> bool {
+    app.execute_contract(sender.clone(), contract.addr().clone(), msg, funds)
+        .is_ok()
+}
+
+/// Runs `actions` against a contract instantiated from `setup`, comparing it with `Model` and
+/// checking the contract invariants after every step.
+pub fn run(setup: &Setup, actions: &[Action]) -> Result<(), TestCaseError> {
+    let senders: Vec<_> = SENDERS
+        .iter()
+        .map(|sender| Addr::unchecked(*sender))
+        .collect();
+    let mut app = App::new(|router, _api, storage| {
+        for sender in &senders {
+            let funds = DENOMS
+                .iter()
+                .map(|denom| Coin::new(INITIAL_BALANCE, *denom))
+                .collect();
+            router.bank.init_balance(storage, sender, funds).unwrap();
+        }
+    });
+    let owner = &senders[0];
+
+    let code_id = CountingContract::store_code(&mut app);
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        owner,
+        0,
+        Coin::new(0, DENOMS[0]),
+        "Parent contract",
+        None,
+        None,
+    )
+    .unwrap();
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        owner,
+        setup.counter,
+        setup.minimal_donation.clone(),
+        "Counting contract",
+        Some(owner),
+        setup.parent.as_ref().map(|parent| Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: parent.period,
+            part: parent.part,
+            remote: None,
+            interval: None,
+        }),
+    )
+    .unwrap();
+
+    let mut model = Model::new(setup);
+    for (step, action) in actions.iter().enumerate() {
+        let accepted = match action {
+            Action::Donate { sender, funds } => execute(
+                &mut app,
+                &senders[*sender],
+                &contract,
+                &ExecMsg::Donate {
+                    memo: None,
+                    on_behalf_of: None,
+                    counter_id: None,
+                },
+                funds,
+            ),
+            Action::Reset { sender, counter } => execute(
+                &mut app,
+                &senders[*sender],
+                &contract,
+                &ExecMsg::Reset {
+                    counter: *counter,
+                    counter_id: None,
+                },
+                &[],
+            ),
+            Action::Withdraw { sender } => execute(
+                &mut app,
+                &senders[*sender],
+                &contract,
+                &ExecMsg::Withdraw {
+                    amount: None,
+                    counter_id: None,
+                    recipient: None,
+                },
+                &[],
+            ),
+            Action::Migrate => app
+                .migrate_contract(owner.clone(), contract.addr().clone(), &Empty {}, code_id)
+                .is_ok(),
+        };
+        let expected = model.apply(action);
+        prop_assert_eq!(accepted, expected, "step {}: {:?}", step, action);
+
+        prop_assert_eq!(
+            contract.query_value(&app)?.value,
+            model.counter,
+            "step {}: counter",
+            step
+        );
+        prop_assert_eq!(
+            parent_contract.query_value(&app)?.value,
+            model.parent_counter,
+            "step {}: parent counter",
+            step
+        );
+        prop_assert_eq!(
+            to_balance(app.wrap().query_all_balances(contract.addr())?),
+            model.balance.clone(),
+            "step {}: contract balance",
+            step
+        );
+        prop_assert_eq!(
+            to_balance(app.wrap().query_all_balances(parent_contract.addr())?),
+            model.parent_balance.clone(),
+            "step {}: parent balance",
+            step
+        );
+        for (sender, wallet) in senders.iter().zip(&model.wallets) {
+            prop_assert_eq!(
+                &to_balance(app.wrap().query_all_balances(sender)?),
+                wallet,
+                "step {}: {} balance",
+                step,
+                sender
+            );
+        }
+
+        for checked in [&contract, &parent_contract] {
+            let violations = checked.query_invariants(&app)?.violations;
+            prop_assert!(
+                violations.is_empty(),
+                "step {}: invariants violated: {:?}",
+                step,
+                violations
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,658 @@
+//! cw-multi-test has no IBC support, so these tests call the IBC entry points directly with
+//! mocked channels and packets, playing the relayer between two counters. Transfers to remote
+//! parents are completed the same way, through `reply` and `sudo`.
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_ibc_channel_connect_ack, mock_ibc_channel_open_init,
+    mock_ibc_channel_open_try, mock_ibc_packet_ack, mock_ibc_packet_recv, mock_ibc_packet_timeout,
+    mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    coin, coins, from_json, to_json_binary, Addr, Binary, CosmosMsg, Decimal, DepsMut, HexBinary,
+    IbcAcknowledgement, IbcMsg, IbcOrder, OwnedDeps, Reply, Response, SubMsg, SubMsgResponse,
+    SubMsgResult,
+};
+
+use crate::transfer::{parse_transfer_sequence, transfer_msg, TRANSFER_REPLY_ID};
+use crate::error::ContractError;
+use crate::events::PARENT_REFUND_EVENT;
+use crate::ibc::{
+    ibc_channel_connect, ibc_channel_open, ibc_packet_ack, ibc_packet_receive, ibc_packet_timeout,
+    IBC_VERSION,
+};
+use crate::msg::{
+    AncestorsResp, CounterPacket, ExecMsg, IbcCounterpartiesResp, IbcCounterparty, IbcHooksResp,
+    IbcLifecycleComplete, InstantiateMsg, ListCountersResp, PacketAck, Parent, ParentStatsResp,
+    QueryMsg, RemoteParent, SudoMsg, TreeEdge, ValueResp,
+};
+use crate::{execute, instantiate, query, reply, sudo};
+
+const ATOM: &str = "atom";
+const CHANNEL: &str = "channel-0";
+const TRANSFER_CHANNEL: &str = "channel-5";
+const REMOTE_PARENT: &str = "osmo1parent";
+/// Connection and counterparty port of the mocked channels.
+const CONNECTION: &str = "connection-2";
+const COUNTERPARTY_PORT: &str = "their_port";
+
+fn instantiated() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        InstantiateMsg {
+            counter: 0,
+            minimal_donation: coin(10, ATOM),
+            parent: None,
+            parent_depth_limit: None,
+            vesting: None,
+            fee: None,
+            fee_admin: None,
+            owner: None,
+            ibc_hooks: false,
+        },
+    )
+    .unwrap();
+
+    deps
+}
+
+fn approve_counterparty(deps: DepsMut) {
+    execute(
+        deps,
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecMsg::UpdateIbcCounterparties {
+            add: vec![IbcCounterparty {
+                connection_id: CONNECTION.to_owned(),
+                port_id: COUNTERPARTY_PORT.to_owned(),
+            }],
+            remove: vec![],
+        },
+    )
+    .unwrap();
+}
+
+fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = instantiated();
+    approve_counterparty(deps.as_mut());
+
+    ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_init(CHANNEL, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+    ibc_channel_connect(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_connect_ack(CHANNEL, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+
+    deps
+}
+
+fn donate(deps: DepsMut) {
+    execute(
+        deps,
+        mock_env(),
+        mock_info("donor", &coins(10, ATOM)),
+        ExecMsg::Donate {
+            memo: None,
+            on_behalf_of: None,
+            counter_id: None,
+        },
+    )
+    .unwrap();
+}
+
+fn sync(deps: DepsMut) -> Result<CounterPacket, ContractError> {
+    let resp = execute(
+        deps,
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecMsg::SyncToRemote {
+            channel: CHANNEL.to_owned(),
+        },
+    )?;
+
+    match &resp.messages[0].msg {
+        CosmosMsg::Ibc(IbcMsg::SendPacket {
+            channel_id, data, ..
+        }) => {
+            assert_eq!(channel_id, CHANNEL);
+            Ok(from_json(data).unwrap())
+        }
+        msg => panic!("Unexpected message: {:?}", msg),
+    }
+}
+
+fn value(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>) -> u64 {
+    let resp: ValueResp =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::Value {}).unwrap()).unwrap();
+    resp.value
+}
+
+#[test]
+fn channel_handshake() {
+    let mut deps = instantiated();
+
+    let err = ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_init(CHANNEL, IbcOrder::Ordered, IBC_VERSION),
+    )
+    .unwrap_err();
+    assert_eq!(ContractError::InvalidIbcOrder {}, err);
+
+    let err = ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_try(CHANNEL, IbcOrder::Unordered, "ics20-1"),
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidIbcVersion {
+            version: "ics20-1".to_owned()
+        },
+        err
+    );
+
+    let err = ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_try(CHANNEL, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::UnapprovedCounterparty {
+            connection: CONNECTION.to_owned(),
+            port: COUNTERPARTY_PORT.to_owned(),
+        },
+        err
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("stranger", &[]),
+        ExecMsg::UpdateIbcCounterparties {
+            add: vec![IbcCounterparty {
+                connection_id: CONNECTION.to_owned(),
+                port_id: COUNTERPARTY_PORT.to_owned(),
+            }],
+            remove: vec![],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::Unauthorized {
+            owner: "owner".to_owned()
+        },
+        err
+    );
+
+    approve_counterparty(deps.as_mut());
+    let resp: IbcCounterpartiesResp =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::IbcCounterparties {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        vec![IbcCounterparty {
+            connection_id: CONNECTION.to_owned(),
+            port_id: COUNTERPARTY_PORT.to_owned(),
+        }],
+        resp.counterparties
+    );
+
+    let resp = ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_try(CHANNEL, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+    assert_eq!(IBC_VERSION, resp.unwrap().version);
+}
+
+#[test]
+fn sync_to_remote() {
+    let mut local = setup();
+    let mut remote = setup();
+
+    let err = sync(local.as_mut()).unwrap_err();
+    assert_eq!(ContractError::NothingToSync {}, err);
+
+    let err = execute(
+        local.as_mut(),
+        mock_env(),
+        mock_info("donor", &[]),
+        ExecMsg::SyncToRemote {
+            channel: CHANNEL.to_owned(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::Unauthorized {
+            owner: "owner".to_owned()
+        },
+        err
+    );
+
+    let err = execute(
+        local.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecMsg::SyncToRemote {
+            channel: "channel-9".to_owned(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::UnknownChannel {
+            channel: "channel-9".to_owned()
+        },
+        err
+    );
+
+    donate(local.as_mut());
+    donate(local.as_mut());
+    donate(remote.as_mut());
+
+    let packet = sync(local.as_mut()).unwrap();
+    assert_eq!(CounterPacket::Increment { by: 2 }, packet);
+
+    let resp = ibc_packet_receive(
+        remote.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(CHANNEL, &packet).unwrap(),
+    )
+    .unwrap();
+    let ack: PacketAck = from_json(&resp.acknowledgement).unwrap();
+    assert!(matches!(ack, PacketAck::Result(_)));
+    assert_eq!(3, value(&remote));
+    assert_eq!(2, value(&local));
+
+    ibc_packet_ack(
+        local.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            CHANNEL,
+            &packet,
+            IbcAcknowledgement::new(resp.acknowledgement),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    // Increments received from the remote chain are not sent back to it
+    let packet = sync(remote.as_mut()).unwrap();
+    assert_eq!(CounterPacket::Increment { by: 1 }, packet);
+
+    let err = sync(local.as_mut()).unwrap_err();
+    assert_eq!(ContractError::NothingToSync {}, err);
+}
+
+#[test]
+fn failed_sync_rolls_back() {
+    let mut local = setup();
+
+    donate(local.as_mut());
+    let packet = sync(local.as_mut()).unwrap();
+    assert_eq!(CounterPacket::Increment { by: 1 }, packet);
+
+    let ack = to_json_binary(&PacketAck::Error("counter missing".to_owned())).unwrap();
+    ibc_packet_ack(
+        local.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(CHANNEL, &packet, IbcAcknowledgement::new(ack)).unwrap(),
+    )
+    .unwrap();
+
+    donate(local.as_mut());
+    let packet = sync(local.as_mut()).unwrap();
+    assert_eq!(CounterPacket::Increment { by: 2 }, packet);
+
+    ibc_packet_timeout(
+        local.as_mut(),
+        mock_env(),
+        mock_ibc_packet_timeout(CHANNEL, &packet).unwrap(),
+    )
+    .unwrap();
+
+    let packet = sync(local.as_mut()).unwrap();
+    assert_eq!(CounterPacket::Increment { by: 2 }, packet);
+}
+
+#[test]
+fn invalid_packet_acks_error() {
+    let mut deps = setup();
+
+    let resp = ibc_packet_receive(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(CHANNEL, &"garbage").unwrap(),
+    )
+    .unwrap();
+    let ack: PacketAck = from_json(&resp.acknowledgement).unwrap();
+    assert!(matches!(ack, PacketAck::Error(_)));
+    assert_eq!(0, value(&deps));
+}
+
+#[test]
+fn received_increment_overflow_acks_error() {
+    let mut deps = setup();
+    donate(deps.as_mut());
+
+    let resp = ibc_packet_receive(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(CHANNEL, &CounterPacket::Increment { by: u64::MAX }).unwrap(),
+    )
+    .unwrap();
+    let ack: PacketAck = from_json(&resp.acknowledgement).unwrap();
+    assert_eq!(
+        PacketAck::Error(ContractError::CounterOverflow {}.to_string()),
+        ack
+    );
+    assert_eq!(1, value(&deps));
+}
+
+fn remote_parent_config() -> Parent {
+    Parent {
+        addr: REMOTE_PARENT.to_owned(),
+        donating_period: 1,
+        part: Decimal::percent(10),
+        remote: Some(RemoteParent {
+            channel: TRANSFER_CHANNEL.to_owned(),
+            timeout: 600,
+        }),
+        interval: None,
+    }
+}
+
+/// Plays the transfer module replying to the transfer with its sequence number.
+fn reply_sequence(deps: DepsMut, sequence: u8) {
+    reply(
+        deps,
+        mock_env(),
+        Reply {
+            id: TRANSFER_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(vec![0x08, sequence])),
+            }),
+        },
+    )
+    .unwrap();
+}
+
+fn set_ibc_hooks(deps: DepsMut, enabled: bool) -> Result<Response, ContractError> {
+    execute(
+        deps,
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecMsg::SetIbcHooks { enabled },
+    )
+}
+
+fn transfer_timeout(deps: DepsMut, sequence: u64) {
+    let resp = sudo(
+        deps,
+        mock_env(),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+            channel: TRANSFER_CHANNEL.to_owned(),
+            sequence,
+        }),
+    )
+    .unwrap();
+    assert!(resp.events.iter().any(|ev| ev.ty == PARENT_REFUND_EVENT));
+}
+
+#[test]
+fn remote_parent() {
+    let mut deps = mock_dependencies();
+
+    let resp = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        InstantiateMsg {
+            counter: 0,
+            minimal_donation: coin(10, ATOM),
+            parent: Some(remote_parent_config()),
+            parent_depth_limit: None,
+            vesting: None,
+            fee: None,
+            fee_admin: None,
+            owner: None,
+            ibc_hooks: true,
+        },
+    )
+    .unwrap();
+    // There is no contract on this chain to register with
+    assert!(resp.messages.is_empty());
+
+    let resp: AncestorsResp = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Ancestors { limit: None },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        vec![TreeEdge {
+            addr: Addr::unchecked(REMOTE_PARENT),
+            forwarded: vec![],
+        }],
+        resp.ancestors
+    );
+
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(100, ATOM));
+    let resp = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("donor", &coins(100, ATOM)),
+        ExecMsg::Donate {
+            memo: None,
+            on_behalf_of: None,
+            counter_id: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        vec![SubMsg::reply_on_success(
+            transfer_msg(
+                &mock_env(),
+                TRANSFER_CHANNEL,
+                REMOTE_PARENT,
+                &coin(10, ATOM),
+                mock_env().block.time.plus_seconds(600),
+            ),
+            TRANSFER_REPLY_ID,
+        )],
+        resp.messages
+    );
+    // ibc-hooks only reports back to the contract named in the memo
+    let memo = format!(r#"{{"ibc_callback":"{}"}}"#, MOCK_CONTRACT_ADDR);
+    match &resp.messages[0].msg {
+        CosmosMsg::Stargate { value, .. } => {
+            assert!(value.as_slice().ends_with(memo.as_bytes()))
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+    reply_sequence(deps.as_mut(), 7);
+
+    sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcAck {
+            channel: TRANSFER_CHANNEL.to_owned(),
+            sequence: 7,
+            ack: "AQ==".to_owned(),
+            success: true,
+        }),
+    )
+    .unwrap();
+
+    // The transfer module sends the funds of a timed out transfer back
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(190, ATOM));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("donor", &coins(100, ATOM)),
+        ExecMsg::Donate {
+            memo: None,
+            on_behalf_of: None,
+            counter_id: None,
+        },
+    )
+    .unwrap();
+    reply_sequence(deps.as_mut(), 8);
+    transfer_timeout(deps.as_mut(), 8);
+
+    let resp: ParentStatsResp =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::ParentStats {}).unwrap()).unwrap();
+    assert_eq!(1, resp.parents.len());
+    assert_eq!(Addr::unchecked(REMOTE_PARENT), resp.parents[0].addr);
+    assert_eq!(coins(10, ATOM), resp.parents[0].forwarded);
+    assert_eq!(1, resp.parents[0].count);
+    assert_eq!(coins(19, ATOM), resp.parents[0].refunded);
+
+    let err = sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::IbcLifecycleComplete(IbcLifecycleComplete::IbcTimeout {
+            channel: TRANSFER_CHANNEL.to_owned(),
+            sequence: 8,
+        }),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
+}
+
+#[test]
+fn transfer_encoding() {
+    // Wire bytes of the `MsgTransfer`, timing out by timestamp only
+    let msg = transfer_msg(
+        &mock_env(),
+        TRANSFER_CHANNEL,
+        REMOTE_PARENT,
+        &coin(10, ATOM),
+        mock_env().block.time.plus_seconds(600),
+    );
+    let expected = HexBinary::from_hex(
+        "0a087472616e7366657212096368616e6e656c2d351a0a0a0461746f6d12023130220f636f736d6f7332636f\
+         6e74726163742a0b6f736d6f31706172656e7438bd82c6cc8abf89e81542227b226962635f63616c6c626163\
+         6b223a22636f736d6f7332636f6e7472616374227d",
+    )
+    .unwrap();
+    assert_eq!(
+        CosmosMsg::Stargate {
+            type_url: "/ibc.applications.transfer.v1.MsgTransfer".to_owned(),
+            value: expected.to_vec().into(),
+        },
+        msg
+    );
+
+    // `MsgTransferResponse` with a sequence spanning two varint bytes, and a truncated one
+    assert_eq!(
+        Some(300),
+        parse_transfer_sequence(&HexBinary::from_hex("08ac02").unwrap())
+    );
+    assert_eq!(None, parse_transfer_sequence(&[0x08, 0xac]));
+}
+
+#[test]
+fn remote_parent_refunds_named_counter() {
+    let mut deps = setup();
+
+    let create = ExecMsg::CreateCounter {
+        id: "named".to_owned(),
+        minimal_donation: coin(10, ATOM),
+        parent: Some(remote_parent_config()),
+        owner: None,
+    };
+    // Without ibc-hooks the transfers would never complete
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        create.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(ContractError::IbcHooksDisabled {}, err);
+
+    set_ibc_hooks(deps.as_mut(), true).unwrap();
+    let resp: IbcHooksResp =
+        from_json(query(deps.as_ref(), mock_env(), QueryMsg::IbcHooks {}).unwrap()).unwrap();
+    assert!(resp.enabled);
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), create).unwrap();
+
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, coins(100, ATOM));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("donor", &coins(100, ATOM)),
+        ExecMsg::Donate {
+            memo: None,
+            on_behalf_of: None,
+            counter_id: Some("named".to_owned()),
+        },
+    )
+    .unwrap();
+    reply_sequence(deps.as_mut(), 1);
+
+    let named_balance = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>| {
+        let resp: ListCountersResp = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ListCounters {
+                    start_after: Some("default".to_owned()),
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        resp.counters[0].withdrawable.clone()
+    };
+    assert_eq!(coins(90, ATOM), named_balance(&deps));
+
+    transfer_timeout(deps.as_mut(), 1);
+    assert_eq!(coins(100, ATOM), named_balance(&deps));
+
+    let err = set_ibc_hooks(deps.as_mut(), false).unwrap_err();
+    assert_eq!(
+        ContractError::RemoteParentInUse {
+            id: "named".to_owned()
+        },
+        err
+    );
+}
+
+#[test]
+fn remote_parent_timeout_must_be_positive() {
+    let mut deps = setup();
+    set_ibc_hooks(deps.as_mut(), true).unwrap();
+
+    let mut parent = remote_parent_config();
+    parent.remote.as_mut().unwrap().timeout = 0;
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        ExecMsg::UpdateParent {
+            parent: Some(parent),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(ContractError::InvalidTransferTimeout {}, err);
+}
@@ -0,0 +1,200 @@
+//! Stand-ins for released versions of the contract, writing the same storage layout and cw2
+//! version as they did, so migrations from them are tested without depending on the old crates.
+//! Donations are only counted, parents are not forwarded to.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    Addr, Binary, Coin, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
+};
+use cw2::set_contract_version;
+use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use serde::{Deserialize, Serialize};
+
+const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
+const OWNER: Item<Addr> = Item::new("owner");
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub counter: u64,
+    pub minimal_donation: Coin,
+}
+
+#[cw_serde]
+pub enum ExecMsg {
+    Donate {},
+}
+
+fn is_counted(minimal_donation: &Coin, funds: &[Coin]) -> bool {
+    minimal_donation.amount.is_zero()
+        || funds.iter().any(|coin| {
+            coin.denom == minimal_donation.denom && coin.amount >= minimal_donation.amount
+        })
+}
+
+fn query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    Err(StdError::generic_err("legacy contracts are not queried"))
+}
+
+/// Version 0.1.0, keeping the counter and the minimal donation in separate items.
+mod v0_1 {
+    use super::*;
+
+    const COUNTER: Item<u64> = Item::new("counter");
+    const MINIMAL_DONATION: Item<Coin> = Item::new("minimal_donation");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        set_contract_version(deps.storage, CONTRACT_NAME, "0.1.0")?;
+        COUNTER.save(deps.storage, &msg.counter)?;
+        MINIMAL_DONATION.save(deps.storage, &msg.minimal_donation)?;
+        OWNER.save(deps.storage, &info.sender)?;
+        Ok(Response::new())
+    }
+
+    pub fn execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        _msg: ExecMsg,
+    ) -> StdResult<Response> {
+        if is_counted(&MINIMAL_DONATION.load(deps.storage)?, &info.funds) {
+            COUNTER.update(deps.storage, |counter| -> StdResult<_> { Ok(counter + 1) })?;
+        }
+        Ok(Response::new())
+    }
+}
+
+/// Version 0.3.0, with a single `state` item and an optional parent.
+mod v0_3 {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct State {
+        counter: u64,
+        minimal_donation: Coin,
+        donating_parent: Option<u64>,
+    }
+
+    const STATE: Item<State> = Item::new("state");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        set_contract_version(deps.storage, CONTRACT_NAME, "0.3.0")?;
+        STATE.save(
+            deps.storage,
+            &State {
+                counter: msg.counter,
+                minimal_donation: msg.minimal_donation,
+                donating_parent: None,
+            },
+        )?;
+        OWNER.save(deps.storage, &info.sender)?;
+        Ok(Response::new())
+    }
+
+    pub fn execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        _msg: ExecMsg,
+    ) -> StdResult<Response> {
+        let mut state = STATE.load(deps.storage)?;
+        if is_counted(&state.minimal_donation, &info.funds) {
+            state.counter += 1;
+            STATE.save(deps.storage, &state)?;
+        }
+        Ok(Response::new())
+    }
+}
+
+/// Version 0.4.0 from before voting weights were tracked. It is the current contract with the
+/// weights of each donation dropped again, so it is instantiated and used as a
+/// `CountingContract`.
+mod v0_4_unweighted {
+    use super::*;
+    use crate::error::ContractError;
+    use crate::state::{TOTAL_WEIGHT, VOTING_WEIGHT};
+
+    pub fn execute(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: crate::msg::ExecMsg,
+    ) -> Result<Response, ContractError> {
+        let resp = crate::execute(deps.branch(), env.clone(), info.clone(), msg)?;
+        VOTING_WEIGHT.remove(deps.storage, &info.sender, env.block.height)?;
+        TOTAL_WEIGHT.remove(deps.storage, env.block.height)?;
+        Ok(resp)
+    }
+}
+
+pub struct LegacyContract(Addr);
+
+impl LegacyContract {
+    pub fn addr(&self) -> &Addr {
+        &self.0
+    }
+
+    pub fn store_code_0_1(app: &mut App) -> u64 {
+        app.store_code(Box::new(ContractWrapper::new(
+            v0_1::execute,
+            v0_1::instantiate,
+            query,
+        )))
+    }
+
+    pub fn store_code_0_3(app: &mut App) -> u64 {
+        app.store_code(Box::new(ContractWrapper::new(
+            v0_3::execute,
+            v0_3::instantiate,
+            query,
+        )))
+    }
+
+    pub fn store_code_0_4_unweighted(app: &mut App) -> u64 {
+        app.store_code(Box::new(ContractWrapper::new(
+            v0_4_unweighted::execute,
+            crate::instantiate,
+            crate::query,
+        )))
+    }
+
+    #[track_caller]
+    pub fn instantiate(
+        app: &mut App,
+        code_id: u64,
+        sender: &Addr,
+        counter: u64,
+        minimal_donation: Coin,
+        admin: &Addr,
+    ) -> StdResult<Self> {
+        app.instantiate_contract(
+            code_id,
+            sender.clone(),
+            &InstantiateMsg {
+                counter,
+                minimal_donation,
+            },
+            &[],
+            "Legacy counting contract",
+            Some(admin.to_string()),
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(LegacyContract)
+    }
+
+    #[track_caller]
+    pub fn donate(&self, app: &mut App, sender: &Addr, funds: &[Coin]) -> StdResult<AppResponse> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Donate {}, funds)
+            .map_err(|err| err.downcast().unwrap())
+    }
+}
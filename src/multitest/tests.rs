@@ -1,18 +1,42 @@
-use cosmwasm_std::{coins, Addr, Coin, Empty, Decimal};
-use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper};
+use cosmwasm_std::{coins, to_json_binary, Addr, Coin, Decimal, Empty, Uint128};
+use cw20::{BalanceResponse, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse};
+use cw_multi_test::{
+    next_block, App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor,
+};
+use cw_utils::{Duration, Expiration};
+use proptest::prelude::*;
 
-use super::contract::CountingContract;
-use crate::msg::Parent;
-use crate::{error::ContractError, execute, instantiate, query};
-use crate::state::{State, STATE};
-use counting_contract_0_1::multitest::contract::CountingContract as CountingContract_0_1;
+#[cfg(feature = "factory")]
+use super::contract::FactoryContract;
+use super::contract::{query_code_id, CountingContract};
+use super::fuzz;
+use super::legacy::LegacyContract;
+#[cfg(feature = "factory")]
+use crate::factory::error::FactoryError;
+use crate::msg::{
+    AccessMode, AllowanceResp, CheckpointPruning, DonationResp, DonorResp, ExecMsg, FeeConfig,
+    GovernanceConfig, InstantiateMsg, MultisigConfig, MultisigInfo, Owner, Parent, ParentForwards,
+    PayoutMode, ProposalStatus, ReceiptConfig, ReceiveMsg, TreeEdge, VestingSchedule,
+};
+use crate::state::{CounterState, COUNTERS, DEFAULT_COUNTER_ID};
+use crate::{error::ContractError, events, execute, instantiate, query};
 
 fn counting_contract() -> Box<dyn Contract<Empty>> {
     let contract = ContractWrapper::new(execute, instantiate, query);
     Box::new(contract)
 }
 
+fn cw20_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
 const ATOM: &str = "atom";
+const OSMO: &str = "osmo";
 
 #[test]
 fn query_value() {
@@ -226,17 +250,16 @@ fn migration() {
             .unwrap();
     });
 
-    let old_code_id = CountingContract_0_1::store_code(&mut app);
+    let old_code_id = LegacyContract::store_code_0_1(&mut app);
     let new_code_id = CountingContract::store_code(&mut app);
 
-    let contract = CountingContract_0_1::instantiate(
+    let contract = LegacyContract::instantiate(
         &mut app,
         old_code_id,
         &owner,
         0,
         Coin::new(10, ATOM),
-        "Counting contract",
-        Some(&admin),
+        &admin,
     )
     .unwrap();
 
@@ -250,15 +273,76 @@ fn migration() {
     let resp = contract.query_value(&app).unwrap();
     assert_eq!(resp.value, 1);
 
-    let state = STATE.query(&app.wrap(), contract.addr().clone()).unwrap();
+    let state = COUNTERS
+        .query(&app.wrap(), contract.addr().clone(), DEFAULT_COUNTER_ID)
+        .unwrap();
     assert_eq!(
         state,
-        State {
+        Some(CounterState {
             counter: 1,
             minimal_donation: Coin::new(10, ATOM),
+            owner,
+            parent: None,
             donating_parent: None,
-        }
+            balance: vec![],
+        })
+    );
+}
+
+#[test]
+fn migration_0_3() {
+    let admin = Addr::unchecked("admin");
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let old_code_id = LegacyContract::store_code_0_3(&mut app);
+    let new_code_id = CountingContract::store_code(&mut app);
+
+    let contract = LegacyContract::instantiate(
+        &mut app,
+        old_code_id,
+        &owner,
+        3,
+        Coin::new(10, ATOM),
+        &admin,
+    )
+    .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let contract =
+        CountingContract::migrate(&mut app, contract.addr().clone(), new_code_id, &admin).unwrap();
+
+    assert_eq!(contract.query_value(&app).unwrap().value, 4);
+    assert_eq!(query_code_id(&app, contract.addr()).unwrap(), new_code_id);
+    // The legacy donation is still held, without having been recorded as received
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(10, ATOM)
     );
+    assert!(contract
+        .query_invariants(&app)
+        .unwrap()
+        .violations
+        .is_empty());
+
+    let fee = FeeConfig {
+        recipient: owner.to_string(),
+        rate: Decimal::percent(5),
+    };
+    let err = contract
+        .update_fee(&mut app, &sender, Some(fee.clone()))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotFeeAdmin {});
+    contract.update_fee(&mut app, &owner, Some(fee)).unwrap();
 }
 
 #[test]
@@ -285,6 +369,59 @@ fn migration_no_update() {
 
 }
 
+#[test]
+fn migration_backfills_voting_weight() {
+    let admin = Addr::unchecked("admin");
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(30, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let old_code_id = LegacyContract::store_code_0_4_unweighted(&mut app);
+    let new_code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        old_code_id,
+        &owner,
+        0,
+        Coin::new(10, ATOM),
+        "Counting contract",
+        Some(&admin),
+        None,
+    )
+    .unwrap();
+    contract.donate(&mut app, &alice, &coins(10, ATOM)).unwrap();
+    contract.donate(&mut app, &alice, &coins(20, ATOM)).unwrap();
+    contract.donate(&mut app, &bob, &coins(20, ATOM)).unwrap();
+    app.update_block(next_block);
+
+    let weight = contract.query_voting_weight(&app, &alice).unwrap();
+    assert_eq!(weight.weight, Uint128::zero());
+
+    // Same version, so only the backfill runs, and running it again changes nothing
+    for _ in 0..2 {
+        CountingContract::migrate(&mut app, contract.addr().clone(), new_code_id, &admin).unwrap();
+        app.update_block(next_block);
+
+        let weight = contract.query_voting_weight(&app, &alice).unwrap();
+        assert_eq!(weight.weight, Uint128::new(30));
+        assert_eq!(weight.total_weight, Uint128::new(50));
+        let weight = contract.query_voting_weight(&app, &bob).unwrap();
+        assert_eq!(weight.weight, Uint128::new(20));
+    }
+}
+
 #[test]
 fn donating_parent() {
     let owner = Addr::unchecked("owner");
@@ -323,6 +460,8 @@ fn donating_parent() {
             addr: parent_contract.addr().to_string(),
             donating_period: 2,
             part: Decimal::percent(10),
+            remote: None,
+            interval: None,
         }),
     )
     .unwrap();
@@ -352,4 +491,2752 @@ fn donating_parent() {
             .unwrap(),
         coins(2, ATOM)
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn parent_cycle() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = App::default();
+    let code_id = CountingContract::store_code(&mut app);
+
+    let first = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "First contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let second = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Second contract",
+        None,
+        Some(Parent {
+            addr: first.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(10),
+            remote: None,
+            interval: None,
+        }),
+    )
+    .unwrap();
+
+    let err = first
+        .update_parent(
+            &mut app,
+            &owner,
+            Some(Parent {
+                addr: second.addr().to_string(),
+                donating_period: 2,
+                part: Decimal::percent(10),
+                remote: None,
+                interval: None,
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ParentCycle {
+            addr: first.addr().to_string()
+        }
+    );
+
+    let err = first
+        .update_parent(
+            &mut app,
+            &owner,
+            Some(Parent {
+                addr: first.addr().to_string(),
+                donating_period: 2,
+                part: Decimal::percent(10),
+                remote: None,
+                interval: None,
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ParentCycle {
+            addr: first.addr().to_string()
+        }
+    );
+
+    assert_eq!(first.query_parent(&app).unwrap().parent, None);
+    assert_eq!(
+        second.query_parent(&app).unwrap().parent,
+        Some(Parent {
+            addr: first.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(10),
+            remote: None,
+            interval: None,
+        })
+    );
+}
+
+#[test]
+fn parent_not_counting_contract() {
+    let owner = Addr::unchecked("owner");
+    let stranger = Addr::unchecked("stranger");
+
+    let mut app = App::default();
+    let code_id = CountingContract::store_code(&mut app);
+
+    let err = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Counting contract",
+        None,
+        Some(Parent {
+            addr: stranger.to_string(),
+            donating_period: 2,
+            part: Decimal::percent(10),
+            remote: None,
+            interval: None,
+        }),
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::InvalidParent(stranger.into_string()));
+}
+
+#[test]
+fn donation_tree() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let root = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Root contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let child = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Child contract",
+        None,
+        Some(Parent {
+            addr: root.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(50),
+            remote: None,
+            interval: None,
+        }),
+    )
+    .unwrap();
+
+    let grandchild = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Grandchild contract",
+        None,
+        Some(Parent {
+            addr: child.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(50),
+            remote: None,
+            interval: None,
+        }),
+    )
+    .unwrap();
+
+    grandchild
+        .donate(&mut app, &sender, &coins(20, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        root.query_children(&app).unwrap().children,
+        vec![TreeEdge {
+            addr: child.addr().clone(),
+            forwarded: coins(5, ATOM),
+        }]
+    );
+    assert_eq!(
+        child.query_children(&app).unwrap().children,
+        vec![TreeEdge {
+            addr: grandchild.addr().clone(),
+            forwarded: coins(10, ATOM),
+        }]
+    );
+    assert_eq!(grandchild.query_children(&app).unwrap().children, vec![]);
+
+    assert_eq!(
+        grandchild.query_ancestors(&app, None).unwrap().ancestors,
+        vec![
+            TreeEdge {
+                addr: child.addr().clone(),
+                forwarded: coins(10, ATOM),
+            },
+            TreeEdge {
+                addr: root.addr().clone(),
+                forwarded: coins(5, ATOM),
+            },
+        ]
+    );
+    assert_eq!(
+        grandchild.query_ancestors(&app, Some(1)).unwrap().ancestors,
+        vec![TreeEdge {
+            addr: child.addr().clone(),
+            forwarded: coins(10, ATOM),
+        }]
+    );
+
+    child.update_parent(&mut app, &owner, None).unwrap();
+    assert_eq!(root.query_children(&app).unwrap().children, vec![]);
+}
+
+#[test]
+fn parent_stats() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Parent contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Child contract",
+        None,
+        Some(Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(50),
+            remote: None,
+            interval: None,
+        }),
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    app.update_block(next_block);
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    let resp = contract.query_parent_stats(&app).unwrap();
+    assert_eq!(
+        resp.parents,
+        vec![ParentForwards {
+            addr: parent_contract.addr().clone(),
+            forwarded: coins(12, ATOM),
+            count: 2,
+            last_height: Some(app.block_info().height),
+            refunded: vec![],
+        }]
+    );
+    assert_eq!(resp.received_from_children, vec![]);
+    assert_eq!(resp.received_from_users, coins(20, ATOM));
+
+    let resp = parent_contract.query_parent_stats(&app).unwrap();
+    assert_eq!(resp.parents, vec![]);
+    assert_eq!(resp.received_from_children, coins(12, ATOM));
+    assert_eq!(resp.received_from_users, vec![]);
+}
+
+#[test]
+fn donate_on_behalf_with_memo() {
+    let owner = Addr::unchecked("owner");
+    let relayer = Addr::unchecked("relayer");
+    let donor = Addr::unchecked("donor");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &relayer, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(10, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    contract
+        .donate_with(
+            &mut app,
+            &relayer,
+            &coins(10, ATOM),
+            Some("for the cause"),
+            Some(&donor),
+        )
+        .unwrap();
+
+    assert_eq!(
+        contract.query_donation(&app, 0).unwrap(),
+        DonationResp {
+            donor: donor.clone(),
+            relayer: Some(relayer.clone()),
+            funds: coins(10, ATOM),
+            memo: Some("for the cause".to_owned()),
+            counted: true,
+            height: app.block_info().height,
+            counter_id: None,
+        }
+    );
+    assert_eq!(
+        contract.query_donor(&app, &donor).unwrap(),
+        DonorResp {
+            donated: coins(10, ATOM),
+            donations: 1,
+        }
+    );
+    assert_eq!(
+        contract.query_donor(&app, &relayer).unwrap(),
+        DonorResp {
+            donated: vec![],
+            donations: 0,
+        }
+    );
+
+    let err = contract
+        .donate_with(
+            &mut app,
+            &relayer,
+            &coins(10, ATOM),
+            Some(&"x".repeat(257)),
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidMemo("longer than 256 bytes".to_owned())
+    );
+}
+
+#[test]
+fn access_list() {
+    let owner = Addr::unchecked("owner");
+    let member1 = Addr::unchecked("member1");
+    let member2 = Addr::unchecked("member2");
+    let stranger = Addr::unchecked("stranger");
+
+    let mut app = App::default();
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(
+        contract.query_access_mode(&app).unwrap().mode,
+        AccessMode::Open
+    );
+
+    let err = contract
+        .set_access_mode(&mut app, &stranger, AccessMode::AllowList)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    contract
+        .set_access_mode(&mut app, &owner, AccessMode::AllowList)
+        .unwrap();
+    contract
+        .update_access_list(&mut app, &owner, &[&member1, &member2, &stranger], &[])
+        .unwrap();
+    contract
+        .update_access_list(&mut app, &owner, &[], &[&stranger])
+        .unwrap();
+
+    contract.donate(&mut app, &member1, &[]).unwrap();
+    let err = contract.donate(&mut app, &stranger, &[]).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DonorNotAllowed {
+            addr: stranger.to_string()
+        }
+    );
+
+    // A registered child forwarding for someone left off the list is blocked as well
+    let child = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &stranger,
+        0,
+        Coin::new(0, ATOM),
+        "Child contract",
+        None,
+        Some(Parent {
+            addr: contract.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(10),
+            remote: None,
+            interval: None,
+        }),
+    )
+    .unwrap();
+    let err = child.donate(&mut app, &stranger, &[]).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DonorNotAllowed {
+            addr: child.addr().to_string()
+        }
+    );
+
+    contract
+        .set_access_mode(&mut app, &owner, AccessMode::DenyList)
+        .unwrap();
+    contract.donate(&mut app, &stranger, &[]).unwrap();
+    let err = contract.donate(&mut app, &member2, &[]).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DonorNotAllowed {
+            addr: member2.to_string()
+        }
+    );
+
+    assert_eq!(contract.query_value(&app).unwrap().value, 2);
+
+    assert_eq!(
+        contract
+            .query_access_list(&app, None, Some(1))
+            .unwrap()
+            .addrs,
+        vec![member1.clone()]
+    );
+    assert_eq!(
+        contract
+            .query_access_list(&app, Some(&member1), None)
+            .unwrap()
+            .addrs,
+        vec![member2]
+    );
+}
+
+#[test]
+fn withdraw_allowance() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let bot = Addr::unchecked("bot");
+    let recipient = Addr::unchecked("recipient");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &sender,
+                vec![Coin::new(20, ATOM), Coin::new(20, OSMO)],
+            )
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    contract
+        .donate(
+            &mut app,
+            &sender,
+            &[Coin::new(20, ATOM), Coin::new(20, OSMO)],
+        )
+        .unwrap();
+
+    let err = contract
+        .increase_allowance(&mut app, &bot, &bot, Coin::new(10, ATOM), None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    let expires = Expiration::AtHeight(app.block_info().height + 1);
+    contract
+        .increase_allowance(&mut app, &owner, &bot, Coin::new(10, ATOM), None)
+        .unwrap();
+    contract
+        .increase_allowance(&mut app, &owner, &bot, Coin::new(5, OSMO), Some(expires))
+        .unwrap();
+    contract
+        .decrease_allowance(&mut app, &owner, &bot, Coin::new(2, ATOM), None)
+        .unwrap();
+
+    let err = contract
+        .withdraw_allowance(&mut app, &bot, Coin::new(0, ATOM), None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::ZeroWithdrawal {});
+
+    contract
+        .withdraw_allowance(&mut app, &bot, Coin::new(5, ATOM), Some(&recipient))
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&recipient).unwrap(),
+        coins(5, ATOM)
+    );
+
+    let err = contract
+        .withdraw_allowance(&mut app, &bot, Coin::new(5, ATOM), None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientAllowance {
+            allowance: Coin::new(3, ATOM)
+        }
+    );
+
+    assert_eq!(
+        contract
+            .query_all_allowances(&app, &bot, None, None)
+            .unwrap()
+            .allowances,
+        vec![
+            AllowanceResp {
+                allowance: Coin::new(3, ATOM),
+                expires: Expiration::Never {},
+            },
+            AllowanceResp {
+                allowance: Coin::new(5, OSMO),
+                expires,
+            },
+        ]
+    );
+
+    app.update_block(next_block);
+    let err = contract
+        .withdraw_allowance(&mut app, &bot, Coin::new(5, OSMO), None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::AllowanceExpired {});
+
+    contract
+        .withdraw_allowance(&mut app, &bot, Coin::new(3, ATOM), None)
+        .unwrap();
+    assert_eq!(
+        contract.query_allowance(&app, &bot, ATOM).unwrap(),
+        AllowanceResp {
+            allowance: Coin::new(0, ATOM),
+            expires: Expiration::Never {},
+        }
+    );
+    assert_eq!(app.wrap().query_all_balances(&bot).unwrap(), coins(3, ATOM));
+}
+
+#[test]
+fn vested_withdraw() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let start = app.block_info().time;
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        &InstantiateMsg {
+            counter: 0,
+            minimal_donation: Coin::new(0, ATOM),
+            parent: None,
+            parent_depth_limit: None,
+            vesting: Some(VestingSchedule::Linear {
+                start,
+                end: start.plus_seconds(100),
+            }),
+            fee: None,
+            fee_admin: None,
+            owner: None,
+            ibc_hooks: false,
+        },
+        "Counting contract",
+        None,
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(100, ATOM))
+        .unwrap();
+    assert_eq!(contract.query_withdrawable(&app).unwrap().amount, vec![]);
+
+    app.update_block(|block| block.time = start.plus_seconds(50));
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(50, ATOM)
+    );
+
+    let err = contract
+        .withdraw_amount(&mut app, &owner, &coins(60, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ExceedsVested {
+            available: Coin::new(50, ATOM)
+        }
+    );
+
+    // Coins of the same denom are added up before checking
+    let err = contract
+        .withdraw_amount(
+            &mut app,
+            &owner,
+            &[Coin::new(30, ATOM), Coin::new(30, ATOM)],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ExceedsVested {
+            available: Coin::new(50, ATOM)
+        }
+    );
+
+    contract
+        .withdraw_amount(&mut app, &owner, &coins(30, ATOM))
+        .unwrap();
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(20, ATOM)
+    );
+
+    app.update_block(|block| block.time = start.plus_seconds(200));
+    contract.withdraw(&mut app, &owner).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(100, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+fn vesting_retained_funds() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let treasury = Addr::unchecked("treasury");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Parent contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let start = app.block_info().time;
+    let err = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        &InstantiateMsg {
+            counter: 0,
+            minimal_donation: Coin::new(0, ATOM),
+            parent: None,
+            parent_depth_limit: None,
+            vesting: Some(VestingSchedule::Linear { start, end: start }),
+            fee: None,
+            fee_admin: None,
+            owner: None,
+            ibc_hooks: false,
+        },
+        "Counting contract",
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidVesting {});
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        &InstantiateMsg {
+            counter: 0,
+            minimal_donation: Coin::new(0, ATOM),
+            parent: Some(Parent {
+                addr: parent_contract.addr().to_string(),
+                donating_period: 1,
+                part: Decimal::percent(50),
+                remote: None,
+                interval: None,
+            }),
+            parent_depth_limit: None,
+            vesting: None,
+            fee: Some(FeeConfig {
+                recipient: treasury.to_string(),
+                rate: Decimal::percent(10),
+            }),
+            fee_admin: None,
+            owner: None,
+            ibc_hooks: false,
+        },
+        "Counting contract",
+        None,
+    )
+    .unwrap();
+
+    let err = contract
+        .update_vesting(
+            &mut app,
+            &sender,
+            Some(VestingSchedule::Cliff {
+                at: start.plus_seconds(100),
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    let err = contract
+        .update_vesting(
+            &mut app,
+            &owner,
+            Some(VestingSchedule::Linear {
+                start: start.plus_seconds(100),
+                end: start,
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidVesting {});
+
+    contract
+        .update_vesting(
+            &mut app,
+            &owner,
+            Some(VestingSchedule::Linear {
+                start,
+                end: start.plus_seconds(100),
+            }),
+        )
+        .unwrap();
+
+    // 10 goes to the fee and half of the remaining 90 to the parent, leaving 45 retained
+    contract
+        .donate(&mut app, &sender, &coins(100, ATOM))
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(45, ATOM)
+    );
+
+    app.update_block(|block| block.time = start.plus_seconds(50));
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(22, ATOM)
+    );
+    contract
+        .withdraw_amount(&mut app, &owner, &coins(22, ATOM))
+        .unwrap();
+
+    app.update_block(|block| block.time = start.plus_seconds(80));
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(14, ATOM)
+    );
+
+    contract.update_vesting(&mut app, &owner, None).unwrap();
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(23, ATOM)
+    );
+}
+
+#[test]
+fn withdraw_to_beneficiaries() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let treasury = Addr::unchecked("treasury");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(15, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let err = contract
+        .set_beneficiaries(
+            &mut app,
+            &owner,
+            &[(&alice, Decimal::percent(30)), (&bob, Decimal::percent(60))],
+            &treasury,
+            PayoutMode::Push,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidShares {
+            total: Decimal::percent(90)
+        }
+    );
+
+    contract
+        .set_beneficiaries(
+            &mut app,
+            &owner,
+            &[(&alice, Decimal::percent(30)), (&bob, Decimal::percent(70))],
+            &treasury,
+            PayoutMode::Push,
+        )
+        .unwrap();
+    assert_eq!(
+        contract.query_beneficiaries(&app).unwrap().remainder,
+        Some(treasury.to_string())
+    );
+
+    contract
+        .donate(&mut app, &sender, &coins(15, ATOM))
+        .unwrap();
+    contract.withdraw(&mut app, &owner).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&alice).unwrap(),
+        coins(4, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&bob).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&treasury).unwrap(),
+        coins(1, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(&owner).unwrap(), vec![]);
+}
+
+#[test]
+fn claim_owed() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let treasury = Addr::unchecked("treasury");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(25, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    contract
+        .set_beneficiaries(
+            &mut app,
+            &owner,
+            &[(&alice, Decimal::percent(30)), (&bob, Decimal::percent(70))],
+            &treasury,
+            PayoutMode::Pull,
+        )
+        .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(15, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        contract.query_claimable(&app, &alice).unwrap().amount,
+        coins(4, ATOM)
+    );
+    assert_eq!(
+        contract.query_claimable(&app, &bob).unwrap().amount,
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        contract.query_claimable(&app, &treasury).unwrap().amount,
+        coins(1, ATOM)
+    );
+    assert_eq!(
+        contract.query_total_owed(&app).unwrap().amount,
+        coins(15, ATOM)
+    );
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(10, ATOM)
+    );
+
+    contract.claim(&mut app, &bob).unwrap();
+    let err = contract.claim(&mut app, &bob).unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+
+    contract.withdraw(&mut app, &owner).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&bob).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(5, ATOM)
+    );
+    assert_eq!(
+        contract.query_total_owed(&app).unwrap().amount,
+        coins(5, ATOM)
+    );
+}
+
+#[test]
+fn donation_fee() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let platform = Addr::unchecked("platform");
+    let treasury = Addr::unchecked("treasury");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(40, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Parent contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        &InstantiateMsg {
+            counter: 0,
+            minimal_donation: Coin::new(0, ATOM),
+            parent: Some(Parent {
+                addr: parent_contract.addr().to_string(),
+                donating_period: 1,
+                part: Decimal::percent(50),
+                remote: None,
+                interval: None,
+            }),
+            parent_depth_limit: None,
+            vesting: None,
+            fee: Some(FeeConfig {
+                recipient: treasury.to_string(),
+                rate: Decimal::percent(10),
+            }),
+            fee_admin: Some(platform.to_string()),
+            owner: None,
+            ibc_hooks: false,
+        },
+        "Counting contract",
+        None,
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(20, ATOM))
+        .unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&treasury).unwrap(),
+        coins(2, ATOM)
+    );
+    assert_eq!(
+        app.wrap()
+            .query_all_balances(parent_contract.addr())
+            .unwrap(),
+        coins(9, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(9, ATOM)
+    );
+
+    let err = contract.update_fee(&mut app, &owner, None).unwrap_err();
+    assert_eq!(err, ContractError::NotFeeAdmin {});
+
+    contract
+        .update_fee(
+            &mut app,
+            &platform,
+            Some(FeeConfig {
+                recipient: treasury.to_string(),
+                rate: Decimal::percent(25),
+            }),
+        )
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(20, ATOM))
+        .unwrap();
+
+    let resp = contract.query_fee(&app).unwrap();
+    assert_eq!(resp.fee_admin, Some(platform));
+    assert_eq!(resp.collected, coins(7, ATOM));
+    assert_eq!(
+        app.wrap().query_all_balances(&treasury).unwrap(),
+        coins(7, ATOM)
+    );
+}
+
+fn event_attr<'a>(resp: &'a AppResponse, ty: &str, key: &str) -> Vec<&'a str> {
+    let ty = format!("wasm-{}", ty);
+    resp.events
+        .iter()
+        .filter(|event| event.ty == ty)
+        .flat_map(|event| &event.attributes)
+        .filter(|attr| attr.key == key)
+        .map(|attr| attr.value.as_str())
+        .collect()
+}
+
+#[test]
+fn typed_events() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &sender,
+                vec![Coin::new(20, ATOM), Coin::new(10, OSMO)],
+            )
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Parent contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Child contract",
+        None,
+        Some(Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(50),
+            remote: None,
+            interval: None,
+        }),
+    )
+    .unwrap();
+
+    let resp = contract
+        .donate(
+            &mut app,
+            &sender,
+            &[Coin::new(20, ATOM), Coin::new(10, OSMO)],
+        )
+        .unwrap();
+
+    assert_eq!(
+        event_attr(&resp, events::DONATE_EVENT, events::AMOUNT_KEY),
+        vec!["20atom,10osmo", "10atom,5osmo"]
+    );
+    assert_eq!(
+        event_attr(&resp, events::DONATE_EVENT, events::DENOMS_KEY),
+        vec!["atom,osmo", "atom,osmo"]
+    );
+    assert_eq!(
+        event_attr(&resp, events::DONATE_EVENT, events::DONOR_KEY),
+        vec![sender.as_str(), contract.addr().as_str()]
+    );
+    assert_eq!(
+        event_attr(&resp, events::PARENT_FORWARD_EVENT, events::PARENT_KEY),
+        vec![parent_contract.addr().as_str()]
+    );
+    assert_eq!(
+        event_attr(&resp, events::PARENT_FORWARD_EVENT, events::AMOUNT_KEY),
+        vec!["10atom,5osmo"]
+    );
+
+    let resp = contract.reset(&mut app, &owner, 10).unwrap();
+    assert_eq!(
+        event_attr(&resp, events::RESET_EVENT, events::COUNTER_KEY),
+        vec!["10"]
+    );
+    assert_eq!(
+        event_attr(&resp, events::RESET_EVENT, events::COUNTER_ID_KEY),
+        vec!["default"]
+    );
+
+    let resp = contract.withdraw(&mut app, &owner).unwrap();
+    assert_eq!(
+        event_attr(&resp, events::WITHDRAW_EVENT, events::RECIPIENT_KEY),
+        vec![owner.as_str()]
+    );
+    assert_eq!(
+        event_attr(&resp, events::WITHDRAW_EVENT, events::AMOUNT_KEY),
+        vec!["10atom,5osmo"]
+    );
+}
+
+#[test]
+fn value_at_height() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+    let start = app.block_info().height;
+
+    app.update_block(next_block);
+    contract.donate(&mut app, &sender, &coins(5, ATOM)).unwrap();
+    contract.donate(&mut app, &sender, &coins(5, ATOM)).unwrap();
+
+    app.update_block(next_block);
+    app.update_block(next_block);
+    contract.reset(&mut app, &owner, 10).unwrap();
+
+    assert_eq!(
+        contract.query_value_at_height(&app, start).unwrap().value,
+        0
+    );
+    assert_eq!(
+        contract
+            .query_value_at_height(&app, start + 1)
+            .unwrap()
+            .value,
+        2
+    );
+    assert_eq!(
+        contract
+            .query_value_at_height(&app, start + 2)
+            .unwrap()
+            .value,
+        2
+    );
+    assert_eq!(
+        contract
+            .query_value_at_height(&app, start + 3)
+            .unwrap()
+            .value,
+        10
+    );
+    assert_eq!(
+        contract
+            .query_value_at_height(&app, start + 100)
+            .unwrap()
+            .value,
+        10
+    );
+    contract.query_value_at_height(&app, start - 1).unwrap_err();
+
+    assert_eq!(
+        contract.query_checkpoint_pruning(&app).unwrap().pruning,
+        CheckpointPruning::Never
+    );
+
+    let err = contract
+        .set_checkpoint_pruning(
+            &mut app,
+            &sender,
+            CheckpointPruning::KeepBlocks { blocks: 1 },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    contract
+        .set_checkpoint_pruning(
+            &mut app,
+            &owner,
+            CheckpointPruning::KeepBlocks { blocks: 1 },
+        )
+        .unwrap();
+    assert_eq!(
+        contract.query_checkpoint_pruning(&app).unwrap().pruning,
+        CheckpointPruning::KeepBlocks { blocks: 1 }
+    );
+
+    app.update_block(next_block);
+    contract.reset(&mut app, &owner, 11).unwrap();
+
+    contract.query_value_at_height(&app, start).unwrap_err();
+    assert_eq!(
+        contract
+            .query_value_at_height(&app, start + 2)
+            .unwrap()
+            .value,
+        2
+    );
+    assert_eq!(
+        contract
+            .query_value_at_height(&app, start + 3)
+            .unwrap()
+            .value,
+        10
+    );
+    assert_eq!(
+        contract
+            .query_value_at_height(&app, start + 4)
+            .unwrap()
+            .value,
+        11
+    );
+
+    // Named counters are checkpointed and pruned on their own
+    contract
+        .create_counter(&mut app, &owner, "named", Coin::new(0, ATOM), None, None)
+        .unwrap();
+    app.update_block(next_block);
+    contract.donate_to(&mut app, &sender, &[], "named").unwrap();
+    app.update_block(next_block);
+    contract.donate_to(&mut app, &sender, &[], "named").unwrap();
+
+    let value = |app: &App, height| {
+        contract
+            .query_counter_value_at_height(app, "named", height)
+            .map(|resp| resp.value)
+    };
+    value(&app, start + 3).unwrap_err();
+    assert_eq!(value(&app, start + 4).unwrap(), 0);
+    assert_eq!(value(&app, start + 5).unwrap(), 1);
+    assert_eq!(value(&app, start + 6).unwrap(), 2);
+    assert_eq!(
+        contract
+            .query_value_at_height(&app, start + 6)
+            .unwrap()
+            .value,
+        11
+    );
+
+    app.update_block(next_block);
+    app.update_block(next_block);
+    contract.donate_to(&mut app, &sender, &[], "named").unwrap();
+    value(&app, start + 4).unwrap_err();
+    assert_eq!(value(&app, start + 7).unwrap(), 2);
+    assert_eq!(value(&app, start + 8).unwrap(), 3);
+}
+
+#[test]
+fn named_counters() {
+    let owner = Addr::unchecked("owner");
+    let counter_owner = Addr::unchecked("counter_owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(30, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Parent contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(10, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let err = contract
+        .create_counter(&mut app, &sender, "named", Coin::new(0, ATOM), None, None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    contract
+        .create_counter(
+            &mut app,
+            &owner,
+            "named",
+            Coin::new(5, ATOM),
+            Some(Parent {
+                addr: parent_contract.addr().to_string(),
+                donating_period: 2,
+                part: Decimal::percent(10),
+                remote: None,
+                interval: None,
+            }),
+            Some(&counter_owner),
+        )
+        .unwrap();
+
+    let err = contract
+        .create_counter(&mut app, &owner, "named", Coin::new(0, ATOM), None, None)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CounterExists {
+            id: "named".to_owned()
+        }
+    );
+
+    let err = contract
+        .create_counter(
+            &mut app,
+            &owner,
+            "no spaces",
+            Coin::new(0, ATOM),
+            None,
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidCounterId(_)));
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate_to(&mut app, &sender, &coins(5, ATOM), "named")
+        .unwrap();
+    let resp = contract
+        .donate_to(&mut app, &sender, &coins(5, ATOM), "named")
+        .unwrap();
+    assert_eq!(
+        event_attr(&resp, events::DONATE_EVENT, events::COUNTER_ID_KEY),
+        vec!["named", DEFAULT_COUNTER_ID]
+    );
+
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+    assert_eq!(
+        app.wrap()
+            .query_all_balances(parent_contract.addr())
+            .unwrap(),
+        coins(1, ATOM)
+    );
+
+    let counters = contract
+        .query_list_counters(&app, None, None)
+        .unwrap()
+        .counters;
+    assert_eq!(counters.len(), 2);
+    assert_eq!(counters[0].id, DEFAULT_COUNTER_ID);
+    assert_eq!(counters[0].counter, 1);
+    assert_eq!(counters[0].owner, owner);
+    assert_eq!(counters[0].withdrawable, coins(10, ATOM));
+    assert_eq!(counters[1].id, "named");
+    assert_eq!(counters[1].counter, 2);
+    assert_eq!(counters[1].owner, counter_owner);
+    assert_eq!(counters[1].withdrawable, coins(9, ATOM));
+
+    let counters = contract
+        .query_list_counters(&app, Some(DEFAULT_COUNTER_ID), Some(1))
+        .unwrap()
+        .counters;
+    assert_eq!(counters.len(), 1);
+    assert_eq!(counters[0].id, "named");
+
+    let err = contract
+        .reset_counter(&mut app, &owner, "named", 10)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: counter_owner.to_string()
+        }
+    );
+
+    let resp = contract
+        .reset_counter(&mut app, &counter_owner, "named", 10)
+        .unwrap();
+    assert_eq!(
+        event_attr(&resp, events::RESET_EVENT, events::COUNTER_ID_KEY),
+        vec!["named"]
+    );
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+
+    let err = contract
+        .withdraw_counter(&mut app, &counter_owner, "named", Some(&coins(10, ATOM)))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ExceedsBalance {
+            available: Coin::new(9, ATOM)
+        }
+    );
+
+    contract
+        .withdraw_counter(&mut app, &counter_owner, "named", None)
+        .unwrap();
+    contract.withdraw(&mut app, &owner).unwrap();
+
+    assert_eq!(
+        app.wrap().query_all_balances(&counter_owner).unwrap(),
+        coins(9, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+}
+
+#[test]
+#[cfg(feature = "factory")]
+fn factory() {
+    let owner = Addr::unchecked("owner");
+    let creator = Addr::unchecked("creator");
+    let other = Addr::unchecked("other");
+
+    let mut app = App::default();
+
+    let counter_code_id = CountingContract::store_code(&mut app);
+    let factory_code_id = FactoryContract::store_code(&mut app);
+
+    let factory = FactoryContract::instantiate(
+        &mut app,
+        factory_code_id,
+        &owner,
+        counter_code_id,
+        "Factory",
+        None,
+    )
+    .unwrap();
+
+    let msg = InstantiateMsg {
+        counter: 0,
+        minimal_donation: Coin::new(10, ATOM),
+        parent: None,
+        parent_depth_limit: None,
+        vesting: None,
+        fee: None,
+        fee_admin: None,
+        owner: None,
+        ibc_hooks: false,
+    };
+    let first = factory
+        .create_counter(&mut app, &creator, msg.clone(), "First", &[])
+        .unwrap();
+    let second = factory
+        .create_counter(&mut app, &creator, msg.clone(), "Second", &[])
+        .unwrap();
+    let third = factory
+        .create_counter(&mut app, &other, msg, "Third", &[])
+        .unwrap();
+
+    let counters = first
+        .query_list_counters(&app, None, None)
+        .unwrap()
+        .counters;
+    assert_eq!(counters[0].owner, creator);
+
+    let mut created: Vec<_> = factory
+        .query_counters_by_creator(&app, &creator, None, None)
+        .unwrap()
+        .counters
+        .into_iter()
+        .map(|entry| entry.addr)
+        .collect();
+    created.sort();
+    let mut expected = vec![first.addr().clone(), second.addr().clone()];
+    expected.sort();
+    assert_eq!(created, expected);
+
+    let all = factory
+        .query_all_counters(&app, None, None)
+        .unwrap()
+        .counters;
+    assert_eq!(all.len(), 3);
+    assert!(all
+        .iter()
+        .any(|entry| entry.addr == *third.addr() && entry.creator == other));
+
+    let new_code_id = CountingContract::store_code(&mut app);
+
+    let err = factory
+        .update_code_id(&mut app, &creator, new_code_id)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        FactoryError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+
+    factory
+        .update_code_id(&mut app, &owner, new_code_id)
+        .unwrap();
+    assert_eq!(
+        factory.query_config(&app).unwrap().counter_code_id,
+        new_code_id
+    );
+
+    factory
+        .migrate_counters(&mut app, &owner, None, Some(2))
+        .unwrap();
+    factory
+        .migrate_counters(&mut app, &owner, Some(&all[1].addr), Some(2))
+        .unwrap();
+
+    for counter in [&first, &second, &third] {
+        assert_eq!(query_code_id(&app, counter.addr()).unwrap(), new_code_id);
+    }
+}
+
+#[test]
+fn receipt_tokens() {
+    let owner = Addr::unchecked("owner");
+    let donor = Addr::unchecked("donor");
+    let other = Addr::unchecked("other");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &donor, coins(35, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &other, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(10, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let cw20_id = app.store_code(cw20_contract());
+    let token = app
+        .instantiate_contract(
+            cw20_id,
+            owner.clone(),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Donation receipt".to_owned(),
+                symbol: "RCPT".to_owned(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: contract.addr().to_string(),
+                    cap: None,
+                }),
+                marketing: None,
+            },
+            &[],
+            "Receipt token",
+            None,
+        )
+        .unwrap();
+
+    let receipt = ReceiptConfig {
+        token: token.to_string(),
+        redeemable: false,
+    };
+    contract
+        .update_receipt(&mut app, &owner, Some(receipt.clone()))
+        .unwrap();
+    assert_eq!(contract.query_receipt(&app).unwrap().receipt, Some(receipt));
+
+    contract.donate(&mut app, &other, &coins(10, ATOM)).unwrap();
+    contract.donate(&mut app, &donor, &coins(30, ATOM)).unwrap();
+    // Not counted, so no receipt
+    contract.donate(&mut app, &donor, &coins(5, ATOM)).unwrap();
+
+    let receipt_balance = |app: &App, addr: &Addr| {
+        let resp: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &token,
+                &Cw20QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap();
+        resp.balance.u128()
+    };
+    assert_eq!(receipt_balance(&app, &other), 10);
+    assert_eq!(receipt_balance(&app, &donor), 30);
+
+    let err = contract.redeem(&mut app, &donor, &token, 15).unwrap_err();
+    assert_eq!(err, ContractError::RedeemDisabled {});
+
+    contract
+        .update_receipt(
+            &mut app,
+            &owner,
+            Some(ReceiptConfig {
+                token: token.to_string(),
+                redeemable: true,
+            }),
+        )
+        .unwrap();
+
+    // 15 of the 40 tokens get 15/40 of the 45 atom held
+    contract.redeem(&mut app, &donor, &token, 15).unwrap();
+    assert_eq!(receipt_balance(&app, &donor), 15);
+    assert_eq!(
+        app.wrap().query_all_balances(&donor).unwrap(),
+        coins(16, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(29, ATOM)
+    );
+
+    let err = app
+        .execute_contract(
+            other.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Receive(Cw20ReceiveMsg {
+                sender: other.to_string(),
+                amount: Uint128::new(10),
+                msg: to_json_binary(&ReceiveMsg::Redeem {}).unwrap(),
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnknownToken {
+            token: other.to_string()
+        }
+    );
+
+    // Under governance funds only leave through passed proposals
+    contract
+        .enable_governance(
+            &mut app,
+            &owner,
+            GovernanceConfig {
+                quorum: Decimal::percent(50),
+                threshold: Decimal::percent(50),
+                voting_period: Duration::Height(10),
+            },
+        )
+        .unwrap();
+    let err = contract.redeem(&mut app, &donor, &token, 5).unwrap_err();
+    assert_eq!(err, ContractError::GovernanceEnabled {});
+}
+
+#[test]
+fn governance() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let recipient = Addr::unchecked("recipient");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &alice, coins(30, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &bob, coins(20, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &carol, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(10, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let err = contract
+        .propose(&mut app, &alice, &recipient, &coins(10, ATOM), "Too early")
+        .unwrap_err();
+    assert_eq!(err, ContractError::GovernanceDisabled {});
+
+    contract.donate(&mut app, &alice, &coins(30, ATOM)).unwrap();
+    contract.donate(&mut app, &bob, &coins(20, ATOM)).unwrap();
+    contract.donate(&mut app, &carol, &coins(10, ATOM)).unwrap();
+    app.update_block(next_block);
+
+    let weight = contract.query_voting_weight(&app, &alice).unwrap();
+    assert_eq!(weight.weight, Uint128::new(30));
+    assert_eq!(weight.total_weight, Uint128::new(60));
+
+    contract
+        .increase_allowance(&mut app, &owner, &carol, Coin::new(5, ATOM), None)
+        .unwrap();
+
+    let err = contract
+        .enable_governance(
+            &mut app,
+            &owner,
+            GovernanceConfig {
+                quorum: Decimal::zero(),
+                threshold: Decimal::percent(60),
+                voting_period: Duration::Height(10),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidGovernanceConfig {});
+
+    contract
+        .enable_governance(
+            &mut app,
+            &owner,
+            GovernanceConfig {
+                quorum: Decimal::percent(50),
+                threshold: Decimal::percent(60),
+                voting_period: Duration::Height(10),
+            },
+        )
+        .unwrap();
+
+    let err = contract.withdraw(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::GovernanceEnabled {});
+
+    // Nor can anyone else move the funds around governance
+    let err = contract
+        .withdraw_allowance(&mut app, &carol, Coin::new(5, ATOM), None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::GovernanceEnabled {});
+
+    let err = contract
+        .set_beneficiaries(
+            &mut app,
+            &owner,
+            &[(&recipient, Decimal::one())],
+            &recipient,
+            PayoutMode::Push,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::GovernanceEnabled {});
+
+    let err = contract.update_parent(&mut app, &owner, None).unwrap_err();
+    assert_eq!(err, ContractError::GovernanceEnabled {});
+
+    let err = contract
+        .propose(&mut app, &recipient, &recipient, &coins(40, ATOM), "Pay me")
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoVotingWeight {});
+
+    contract
+        .propose(
+            &mut app,
+            &alice,
+            &recipient,
+            &coins(40, ATOM),
+            "Pay the recipient",
+        )
+        .unwrap();
+
+    let err = contract.vote(&mut app, &alice, 1, false).unwrap_err();
+    assert_eq!(err, ContractError::AlreadyVoted { id: 1 });
+
+    // 30 of 60 yes is not enough for the 60% threshold yet
+    let err = contract.execute_proposal(&mut app, &alice, 1).unwrap_err();
+    assert_eq!(err, ContractError::ProposalNotPassed { id: 1 });
+
+    contract.vote(&mut app, &bob, 1, false).unwrap();
+    let proposal = contract.query_proposal(&app, 1).unwrap();
+    assert_eq!(proposal.status, ProposalStatus::Open);
+    assert_eq!(proposal.yes, Uint128::new(30));
+    assert_eq!(proposal.no, Uint128::new(20));
+
+    contract.vote(&mut app, &carol, 1, true).unwrap();
+    let proposal = contract.query_proposal(&app, 1).unwrap();
+    assert_eq!(proposal.status, ProposalStatus::Passed);
+
+    contract.execute_proposal(&mut app, &bob, 1).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&recipient).unwrap(),
+        coins(40, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(20, ATOM)
+    );
+
+    let proposal = contract.query_proposal(&app, 1).unwrap();
+    assert_eq!(proposal.status, ProposalStatus::Executed);
+    let err = contract.execute_proposal(&mut app, &bob, 1).unwrap_err();
+    assert_eq!(err, ContractError::ProposalNotPassed { id: 1 });
+
+    let votes = contract.query_list_votes(&app, 1, None, None).unwrap();
+    assert_eq!(
+        votes
+            .votes
+            .iter()
+            .map(|vote| (vote.voter.as_str(), vote.yes, vote.weight.u128()))
+            .collect::<Vec<_>>(),
+        vec![("alice", true, 30), ("bob", false, 20), ("carol", true, 10)]
+    );
+
+    // Without quorum the proposal is rejected once voting ends
+    contract
+        .propose(&mut app, &bob, &recipient, &coins(10, ATOM), "Pay again")
+        .unwrap();
+    app.update_block(|block| block.height += 10);
+
+    let proposal = contract.query_proposal(&app, 2).unwrap();
+    assert_eq!(proposal.status, ProposalStatus::Rejected);
+    let err = contract.vote(&mut app, &carol, 2, true).unwrap_err();
+    assert_eq!(err, ContractError::ProposalNotOpen { id: 2 });
+}
+
+#[test]
+fn multisig_owner() {
+    let owner = Addr::unchecked("owner");
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let carol = Addr::unchecked("carol");
+    let donor = Addr::unchecked("donor");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &donor, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(10, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    contract.donate(&mut app, &donor, &coins(10, ATOM)).unwrap();
+
+    let err = contract
+        .update_owner(
+            &mut app,
+            &owner,
+            Owner::Multisig(MultisigConfig {
+                members: vec![alice.to_string(), bob.to_string(), alice.to_string()],
+                threshold: 2,
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMultisig {});
+
+    contract
+        .update_owner(
+            &mut app,
+            &owner,
+            Owner::Multisig(MultisigConfig {
+                members: vec![alice.to_string(), bob.to_string(), carol.to_string()],
+                threshold: 2,
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        contract.query_multisig(&app).unwrap().multisig,
+        Some(MultisigInfo {
+            members: vec![alice.clone(), bob.clone(), carol.clone()],
+            threshold: 2,
+        })
+    );
+
+    let err = contract.withdraw(&mut app, &owner).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: contract.addr().to_string()
+        }
+    );
+    let err = contract.withdraw(&mut app, &alice).unwrap_err();
+    assert_eq!(err, ContractError::MultisigApprovalRequired {});
+    let err = contract.reset(&mut app, &bob, 5).unwrap_err();
+    assert_eq!(err, ContractError::MultisigApprovalRequired {});
+
+    let withdraw = ExecMsg::Withdraw {
+        amount: None,
+        counter_id: None,
+        recipient: Some(alice.to_string()),
+    };
+    let err = contract
+        .propose_owner_action(&mut app, &owner, withdraw.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotMultisigMember {});
+
+    // Only owner gated messages, and no withdrawals back to the contract
+    let err = contract
+        .propose_owner_action(
+            &mut app,
+            &alice,
+            ExecMsg::Donate {
+                memo: None,
+                on_behalf_of: None,
+                counter_id: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidOwnerAction {});
+    let err = contract
+        .propose_owner_action(
+            &mut app,
+            &alice,
+            ExecMsg::ProposeOwnerAction {
+                action: Box::new(withdraw.clone()),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidOwnerAction {});
+    let err = contract
+        .propose_owner_action(
+            &mut app,
+            &alice,
+            ExecMsg::Withdraw {
+                amount: None,
+                counter_id: None,
+                recipient: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::WithdrawToSelf {});
+
+    contract
+        .propose_owner_action(&mut app, &alice, withdraw.clone())
+        .unwrap();
+    let err = contract
+        .approve_owner_action(&mut app, &alice, 1)
+        .unwrap_err();
+    assert_eq!(err, ContractError::AlreadyApproved { id: 1 });
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(10, ATOM)
+    );
+
+    contract.approve_owner_action(&mut app, &carol, 1).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&alice).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        vec![]
+    );
+
+    let action = contract.query_owner_action(&app, 1).unwrap();
+    assert_eq!(action.action, withdraw);
+    assert_eq!(action.approvals, vec![alice.clone(), carol.clone()]);
+    assert!(action.executed);
+
+    let err = contract
+        .approve_owner_action(&mut app, &bob, 1)
+        .unwrap_err();
+    assert_eq!(err, ContractError::OwnerActionExecuted { id: 1 });
+
+    // Back to a single owner
+    contract
+        .propose_owner_action(
+            &mut app,
+            &bob,
+            ExecMsg::UpdateOwner {
+                owner: Owner::Single {
+                    addr: owner.to_string(),
+                },
+            },
+        )
+        .unwrap();
+    contract.approve_owner_action(&mut app, &alice, 2).unwrap();
+
+    assert_eq!(contract.query_multisig(&app).unwrap().multisig, None);
+    contract.reset(&mut app, &owner, 5).unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 5);
+    let err = contract.reset(&mut app, &alice, 0).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Unauthorized {
+            owner: owner.to_string()
+        }
+    );
+}
+
+#[test]
+fn subscriptions() {
+    let owner = Addr::unchecked("owner");
+    let donor = Addr::unchecked("donor");
+    let other = Addr::unchecked("other");
+    let keeper = Addr::unchecked("keeper");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &donor, coins(30, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &other, coins(10, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(10, ATOM),
+        "Counting contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let err = contract
+        .subscribe(
+            &mut app,
+            &donor,
+            Coin::new(10, ATOM),
+            100,
+            3,
+            &coins(20, ATOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidSubscriptionDeposit {
+            expected: Coin::new(30, ATOM)
+        }
+    );
+
+    let start = app.block_info().time;
+    contract
+        .subscribe(
+            &mut app,
+            &donor,
+            Coin::new(10, ATOM),
+            100,
+            3,
+            &coins(30, ATOM),
+        )
+        .unwrap();
+    contract
+        .subscribe(
+            &mut app,
+            &other,
+            Coin::new(5, ATOM),
+            50,
+            2,
+            &coins(10, ATOM),
+        )
+        .unwrap();
+    assert_eq!(contract.query_next_due(&app).unwrap().next_due, Some(start));
+
+    // Deposits are not part of the counter funds until donated
+    assert_eq!(contract.query_withdrawable(&app).unwrap().amount, vec![]);
+
+    contract
+        .process_subscriptions(&mut app, &keeper, None)
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(15, ATOM)
+    );
+    assert_eq!(
+        contract.query_next_due(&app).unwrap().next_due,
+        Some(start.plus_seconds(50))
+    );
+
+    // Nothing due until the next period
+    contract
+        .process_subscriptions(&mut app, &keeper, None)
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 1);
+
+    app.update_block(|block| block.time = start.plus_seconds(100));
+    contract
+        .process_subscriptions(&mut app, &keeper, Some(1))
+        .unwrap();
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(20, ATOM)
+    );
+    contract
+        .process_subscriptions(&mut app, &keeper, None)
+        .unwrap();
+    assert_eq!(contract.query_value(&app).unwrap().value, 2);
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(30, ATOM)
+    );
+
+    // The second subscription is done
+    let subscriptions = contract.query_list_subscriptions(&app, None).unwrap();
+    assert_eq!(subscriptions.subscriptions.len(), 1);
+    let subscription = contract.query_subscription(&app, 1).unwrap();
+    assert_eq!(subscription.remaining, 1);
+    assert_eq!(subscription.next_due, start.plus_seconds(200));
+    assert_eq!(
+        contract
+            .query_list_subscriptions(&app, Some(&other))
+            .unwrap()
+            .subscriptions,
+        vec![]
+    );
+
+    let err = contract
+        .cancel_subscription(&mut app, &other, 1)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotSubscriber { id: 1 });
+
+    contract.cancel_subscription(&mut app, &donor, 1).unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&donor).unwrap(),
+        coins(10, ATOM)
+    );
+    assert_eq!(contract.query_next_due(&app).unwrap().next_due, None);
+    assert_eq!(
+        contract.query_withdrawable(&app).unwrap().amount,
+        coins(30, ATOM)
+    );
+
+    let donor_stats = contract.query_donor(&app, &donor).unwrap();
+    assert_eq!(donor_stats.donations, 2);
+}
+
+#[test]
+fn flush_to_parent() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let keeper = Addr::unchecked("keeper");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(40, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Parent contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Child contract",
+        None,
+        Some(Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 2,
+            part: Decimal::percent(50),
+            remote: None,
+            interval: None,
+        }),
+    )
+    .unwrap();
+
+    let err = contract
+        .update_flush_bounty(&mut app, &owner, Some(Decimal::one()))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFlushBounty {});
+
+    contract
+        .update_flush_bounty(&mut app, &owner, Some(Decimal::percent(10)))
+        .unwrap();
+
+    let err = contract.flush_to_parent(&mut app, &keeper).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingForward {});
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+
+    // The period ended, but nothing is forwarded until flushed
+    assert_eq!(parent_contract.query_value(&app).unwrap().value, 0);
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(20, ATOM)
+    );
+    let resp = contract.query_flush_bounty(&app).unwrap();
+    assert_eq!(resp.bounty, Some(Decimal::percent(10)));
+    assert!(resp.pending);
+
+    contract.flush_to_parent(&mut app, &keeper).unwrap();
+
+    assert_eq!(parent_contract.query_value(&app).unwrap().value, 1);
+    assert_eq!(
+        app.wrap().query_all_balances(&keeper).unwrap(),
+        coins(1, ATOM)
+    );
+    assert_eq!(
+        app.wrap()
+            .query_all_balances(parent_contract.addr())
+            .unwrap(),
+        coins(9, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_all_balances(contract.addr()).unwrap(),
+        coins(10, ATOM)
+    );
+    assert!(!contract.query_flush_bounty(&app).unwrap().pending);
+
+    let err = contract.flush_to_parent(&mut app, &keeper).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingForward {});
+
+    // Named counters wait for a flush just the same, tracked apart from the default one
+    contract
+        .create_counter(
+            &mut app,
+            &owner,
+            "named",
+            Coin::new(0, ATOM),
+            Some(Parent {
+                addr: parent_contract.addr().to_string(),
+                donating_period: 1,
+                part: Decimal::percent(50),
+                remote: None,
+                interval: None,
+            }),
+            None,
+        )
+        .unwrap();
+    contract
+        .donate_to(&mut app, &sender, &coins(20, ATOM), "named")
+        .unwrap();
+    assert_eq!(parent_contract.query_value(&app).unwrap().value, 1);
+    assert!(
+        contract
+            .query_counter_flush_bounty(&app, "named")
+            .unwrap()
+            .pending
+    );
+    assert!(!contract.query_flush_bounty(&app).unwrap().pending);
+    let err = contract.flush_to_parent(&mut app, &keeper).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingForward {});
+
+    contract
+        .flush_counter_to_parent(&mut app, &keeper, "named")
+        .unwrap();
+    assert_eq!(parent_contract.query_value(&app).unwrap().value, 2);
+    assert_eq!(
+        app.wrap().query_all_balances(&keeper).unwrap(),
+        coins(2, ATOM)
+    );
+    assert_eq!(
+        app.wrap()
+            .query_all_balances(parent_contract.addr())
+            .unwrap(),
+        coins(18, ATOM)
+    );
+    let counters = contract
+        .query_list_counters(&app, Some("default"), None)
+        .unwrap()
+        .counters;
+    assert_eq!(counters[0].withdrawable, coins(10, ATOM));
+    assert!(
+        !contract
+            .query_counter_flush_bounty(&app, "named")
+            .unwrap()
+            .pending
+    );
+    contract.assert_invariants(&app);
+}
+
+#[test]
+fn parent_interval() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let keeper = Addr::unchecked("keeper");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(20, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Parent contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let start = app.block_info().time;
+    let contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Child contract",
+        None,
+        Some(Parent {
+            addr: parent_contract.addr().to_string(),
+            donating_period: 1,
+            part: Decimal::percent(10),
+            remote: None,
+            interval: Some(Duration::Time(100)),
+        }),
+    )
+    .unwrap();
+
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(parent_contract.query_value(&app).unwrap().value, 0);
+    assert_eq!(
+        contract.query_parent(&app).unwrap().next_forward,
+        Some(Expiration::AtTime(start.plus_seconds(100)))
+    );
+
+    let err = contract.flush_to_parent(&mut app, &keeper).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingForward {});
+
+    app.update_block(|block| block.time = start.plus_seconds(100));
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    assert_eq!(parent_contract.query_value(&app).unwrap().value, 1);
+    assert_eq!(
+        app.wrap()
+            .query_all_balances(parent_contract.addr())
+            .unwrap(),
+        coins(2, ATOM)
+    );
+    assert_eq!(
+        contract.query_parent(&app).unwrap().next_forward,
+        Some(Expiration::AtTime(start.plus_seconds(200)))
+    );
+
+    // Without donations, anyone can forward once the interval passed
+    app.update_block(|block| block.time = start.plus_seconds(200));
+    contract.flush_to_parent(&mut app, &keeper).unwrap();
+    assert_eq!(parent_contract.query_value(&app).unwrap().value, 2);
+    assert_eq!(
+        app.wrap()
+            .query_all_balances(parent_contract.addr())
+            .unwrap(),
+        coins(3, ATOM)
+    );
+    assert_eq!(app.wrap().query_all_balances(&keeper).unwrap(), vec![]);
+    assert_eq!(
+        contract.query_parent(&app).unwrap().next_forward,
+        Some(Expiration::AtTime(start.plus_seconds(300)))
+    );
+}
+
+#[test]
+fn invariants() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+    let alice = Addr::unchecked("alice");
+    let treasury = Addr::unchecked("treasury");
+    let platform = Addr::unchecked("platform");
+    let keeper = Addr::unchecked("keeper");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(100, ATOM))
+            .unwrap();
+    });
+
+    let code_id = CountingContract::store_code(&mut app);
+
+    let parent_contract = CountingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        0,
+        Coin::new(0, ATOM),
+        "Parent contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let contract = CountingContract::instantiate_with_msg(
+        &mut app,
+        code_id,
+        &owner,
+        &InstantiateMsg {
+            counter: 0,
+            minimal_donation: Coin::new(0, ATOM),
+            parent: Some(Parent {
+                addr: parent_contract.addr().to_string(),
+                donating_period: 2,
+                part: Decimal::percent(50),
+                remote: None,
+                interval: None,
+            }),
+            parent_depth_limit: None,
+            vesting: None,
+            fee: None,
+            fee_admin: Some(platform.to_string()),
+            owner: None,
+            ibc_hooks: false,
+        },
+        "Child contract",
+        None,
+    )
+    .unwrap();
+    contract.assert_invariants(&app);
+
+    contract
+        .update_fee(
+            &mut app,
+            &platform,
+            Some(FeeConfig {
+                recipient: treasury.to_string(),
+                rate: Decimal::percent(10),
+            }),
+        )
+        .unwrap();
+
+    // The second donation forwards to the parent
+    for _ in 0..2 {
+        contract
+            .donate(&mut app, &sender, &coins(10, ATOM))
+            .unwrap();
+        contract.assert_invariants(&app);
+        parent_contract.assert_invariants(&app);
+    }
+
+    contract.withdraw(&mut app, &owner).unwrap();
+    contract.assert_invariants(&app);
+
+    contract
+        .create_counter(&mut app, &owner, "named", Coin::new(0, ATOM), None, None)
+        .unwrap();
+    contract
+        .donate_to(&mut app, &sender, &coins(10, ATOM), "named")
+        .unwrap();
+    contract.assert_invariants(&app);
+    contract
+        .withdraw_counter(&mut app, &owner, "named", Some(&coins(4, ATOM)))
+        .unwrap();
+    contract.assert_invariants(&app);
+
+    // Half of the deposit stays escrowed
+    contract
+        .subscribe(
+            &mut app,
+            &sender,
+            Coin::new(10, ATOM),
+            100,
+            2,
+            &coins(20, ATOM),
+        )
+        .unwrap();
+    contract.assert_invariants(&app);
+    contract
+        .process_subscriptions(&mut app, &keeper, None)
+        .unwrap();
+    contract.assert_invariants(&app);
+    parent_contract.assert_invariants(&app);
+
+    contract
+        .set_beneficiaries(
+            &mut app,
+            &owner,
+            &[(&alice, Decimal::one())],
+            &treasury,
+            PayoutMode::Pull,
+        )
+        .unwrap();
+    contract
+        .donate(&mut app, &sender, &coins(10, ATOM))
+        .unwrap();
+    contract.assert_invariants(&app);
+    contract.claim(&mut app, &alice).unwrap();
+    contract.assert_invariants(&app);
+
+    // Funds sent without donating are not accounted for
+    app.send_tokens(sender.clone(), contract.addr().clone(), &coins(5, ATOM))
+        .unwrap();
+    let violations = contract.query_invariants(&app).unwrap().violations;
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0].starts_with("balance, forwards and withdrawals add up to"));
+}
+
+#[test]
+#[cfg(not(feature = "ibc"))]
+fn ibc_disabled() {
+    let mut app = App::default();
+    let owner = Addr::unchecked("owner");
+
+    let contract_id = app.store_code(counting_contract());
+    let contract = CountingContract::instantiate(
+        &mut app,
+        contract_id,
+        &owner,
+        0,
+        Coin::new(10, ATOM),
+        "Counting Contract",
+        None,
+        None,
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            contract.addr().clone(),
+            &ExecMsg::SyncToRemote {
+                channel: "channel-0".to_owned(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IbcDisabled {},
+        err.downcast::<ContractError>().unwrap()
+    );
+
+    // Enabling ibc-hooks doesn't help, transfers to remote parents are not built in
+    app.execute_contract(
+        owner.clone(),
+        contract.addr().clone(),
+        &ExecMsg::SetIbcHooks { enabled: true },
+        &[],
+    )
+    .unwrap();
+    let parent = Parent {
+        addr: "osmo1parent".to_owned(),
+        donating_period: 1,
+        part: Decimal::percent(10),
+        remote: Some(crate::msg::RemoteParent {
+            channel: "channel-5".to_owned(),
+            timeout: 600,
+        }),
+        interval: None,
+    };
+    let err = contract
+        .update_parent(&mut app, &owner, Some(parent))
+        .unwrap_err();
+    assert_eq!(ContractError::IbcDisabled {}, err);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn matches_model(setup in fuzz::setup(), actions in fuzz::actions(30)) {
+        fuzz::run(&setup, &actions)?;
+    }
+}
@@ -0,0 +1,50 @@
+//! Protobuf messages of the ICS-20 transfer module, as far as the contract sends or reads them.
+//! Field numbers follow `ibc/applications/transfer/v1/tx.proto` of ibc-go.
+
+use prost::Message;
+
+pub const MSG_TRANSFER_TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransfer";
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoCoin {
+    #[prost(string, tag = "1")]
+    pub denom: String,
+    #[prost(string, tag = "2")]
+    pub amount: String,
+}
+
+/// `ibc.core.client.v1.Height`, left unset as transfers time out by timestamp only.
+#[derive(Clone, PartialEq, Message)]
+pub struct Height {
+    #[prost(uint64, tag = "1")]
+    pub revision_number: u64,
+    #[prost(uint64, tag = "2")]
+    pub revision_height: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgTransfer {
+    #[prost(string, tag = "1")]
+    pub source_port: String,
+    #[prost(string, tag = "2")]
+    pub source_channel: String,
+    #[prost(message, optional, tag = "3")]
+    pub token: Option<ProtoCoin>,
+    #[prost(string, tag = "4")]
+    pub sender: String,
+    #[prost(string, tag = "5")]
+    pub receiver: String,
+    #[prost(message, optional, tag = "6")]
+    pub timeout_height: Option<Height>,
+    /// Nanoseconds since the unix epoch.
+    #[prost(uint64, tag = "7")]
+    pub timeout_timestamp: u64,
+    #[prost(string, tag = "8")]
+    pub memo: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MsgTransferResponse {
+    #[prost(uint64, tag = "1")]
+    pub sequence: u64,
+}
@@ -1,21 +1,290 @@
-use cosmwasm_std::{Coin, Addr, Decimal};
-use cw_storage_plus::Item;
+#[cfg(feature = "ibc")]
+use cosmwasm_std::IbcEndpoint;
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, Timestamp, Uint128};
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
+use cw_utils::{Duration, Expiration};
 use serde::{Deserialize, Serialize};
 
+use crate::msg::{
+    AccessMode, CheckpointPruning, ExecMsg, GovernanceConfig, PayoutMode, ProposalStatus,
+    RemoteParent, VestingSchedule,
+};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-pub struct State {
+pub struct ParentDonation {
+    /// Address on the parent's chain, not validated when it is `remote`.
+    pub address: Addr,
+    pub donating_parent_period: u64,
+    pub part: Decimal,
+    #[serde(default)]
+    pub remote: Option<RemoteParent>,
+    #[serde(default)]
+    pub interval: Option<Duration>,
+}
+
+/// Single counter held by the contract. Features beyond counting, withdrawing and forwarding
+/// to a parent only apply to the default counter, see `ExecMsg::CreateCounter`. It owns
+/// whatever the contract holds outside of named counters' `balance`. Named counters keep their
+/// donations in `balance`, always empty for the default.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CounterState {
     pub counter: u64,
     pub minimal_donation: Coin,
+    pub owner: Addr,
+    pub parent: Option<ParentDonation>,
     pub donating_parent: Option<u64>,
+    pub balance: Vec<Coin>,
+}
+
+/// Everything this contract has forwarded to a single parent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct ForwardStats {
+    pub forwarded: Vec<Coin>,
+    pub count: u64,
+    pub last_height: Option<u64>,
+    #[serde(default)]
+    pub refunded: Vec<Coin>,
+}
+
+/// Funds received through `Donate`, split by whether they came from a registered child.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct Received {
+    pub from_children: Vec<Coin>,
+    pub from_users: Vec<Coin>,
 }
 
+/// Single `Donate` call, credited to `donor` even when a relayer sent it.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
-pub struct ParentDonation {
-    pub address: Addr,
-    pub donating_parent_period: u64,
-    pub part: Decimal,
+pub struct Donation {
+    pub donor: Addr,
+    pub relayer: Option<Addr>,
+    pub funds: Vec<Coin>,
+    pub memo: Option<String>,
+    pub counted: bool,
+    pub height: u64,
+    /// Named counter the donation went to, `None` for the default one.
+    #[serde(default)]
+    pub counter_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub struct DonorStats {
+    pub donated: Vec<Coin>,
+    pub donations: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Allowance {
+    pub amount: Uint128,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BeneficiaryShare {
+    pub addr: Addr,
+    pub share: Decimal,
+}
+
+/// Split of owner withdrawals or of counted donations, depending on `mode`. Shares add up to
+/// exactly one; whatever rounding leaves over goes to `remainder`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Payout {
+    pub beneficiaries: Vec<BeneficiaryShare>,
+    pub remainder: Addr,
+    pub mode: PayoutMode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Fee {
+    pub recipient: Addr,
+    pub rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Receipt {
+    pub token: Addr,
+    pub redeemable: bool,
+}
+
+/// Counter used when a message carries no `counter_id`, and the only one before 0.4.0.
+pub const DEFAULT_COUNTER_ID: &str = "default";
+pub const COUNTERS: Map<&str, CounterState> = Map::new("counters");
+/// Sum of all named counters' balances.
+pub const NAMED_BALANCE: Item<Vec<Coin>> = Item::new("named_balance");
+
+pub const DEFAULT_PARENT_DEPTH_LIMIT: u32 = 16;
+pub const PARENT_DEPTH_LIMIT: Item<u32> = Item::new("parent_depth_limit");
+
+pub const CHILDREN: Map<&Addr, Vec<Coin>> = Map::new("children");
+pub const FORWARDED: Map<&Addr, ForwardStats> = Map::new("forwarded");
+pub const RECEIVED: Item<Received> = Item::new("received");
+
+pub const DONATION_COUNT: Item<u64> = Item::new("donation_count");
+pub const DONATIONS: Map<u64, Donation> = Map::new("donations");
+pub const DONORS: Map<&Addr, DonorStats> = Map::new("donors");
+/// Donations to named counters, which `RECEIVED` leaves out. Only used to check the accounting.
+pub const NAMED_RECEIVED: Item<Vec<Coin>> = Item::new("named_received");
+/// Funds held when migrating from a version that did not record what it received. Only used to
+/// check the accounting.
+pub const MIGRATED_BALANCE: Item<Vec<Coin>> = Item::new("migrated_balance");
+
+pub const ACCESS_MODE: Item<AccessMode> = Item::new("access_mode");
+pub const ACCESS_LIST: Map<&Addr, Empty> = Map::new("access_list");
+
+/// Allowances granted by the owner, keyed by spender and denom.
+pub const ALLOWANCES: Map<(&Addr, &str), Allowance> = Map::new("allowances");
+
+pub const VESTING: Item<VestingSchedule> = Item::new("vesting");
+pub const WITHDRAWN: Item<Vec<Coin>> = Item::new("withdrawn");
+/// Funds withdrawn from named counters, which `WITHDRAWN` leaves out. Only used to check the
+/// accounting.
+pub const NAMED_WITHDRAWN: Item<Vec<Coin>> = Item::new("named_withdrawn");
+
+pub const PAYOUT: Item<Payout> = Item::new("payout");
+pub const OWED: Map<&Addr, Vec<Coin>> = Map::new("owed");
+pub const TOTAL_OWED: Item<Vec<Coin>> = Item::new("total_owed");
+
+pub const FEE: Item<Fee> = Item::new("fee");
+pub const FEE_ADMIN: Item<Addr> = Item::new("fee_admin");
+pub const FEES_COLLECTED: Item<Vec<Coin>> = Item::new("fees_collected");
+/// Funds paid out by beneficiary claims. Only used to check the accounting.
+pub const CLAIMED: Item<Vec<Coin>> = Item::new("claimed");
+
+/// Counter values by counter id, with the value before every block it changed in.
+pub const COUNTER_CHECKPOINTS: SnapshotMap<&str, u64> = SnapshotMap::new(
+    "counter_checkpoints",
+    "counter_checkpoints__checkpoints",
+    "counter_checkpoints__changelog",
+    Strategy::EveryBlock,
+);
+/// Highest changelog height pruned from `COUNTER_CHECKPOINTS` by counter id. Values at the
+/// end of blocks below it are no longer known.
+pub const PRUNED_CHECKPOINTS: Map<&str, u64> = Map::new("pruned_checkpoints");
+pub const CHECKPOINT_PRUNING: Item<CheckpointPruning> = Item::new("checkpoint_pruning");
+
+/// Connected IBC channels, with the counterparty endpoint, keyed by our channel id.
+#[cfg(feature = "ibc")]
+pub const IBC_CHANNELS: Map<&str, IbcEndpoint> = Map::new("ibc_channels");
+/// Counterparties the owner allows to open channels, keyed by connection id and port id.
+pub const IBC_COUNTERPARTIES: Map<(&str, &str), Empty> = Map::new("ibc_counterparties");
+/// Set when the owner declared the chain runs ibc-hooks, which remote parents rely on.
+pub const IBC_HOOKS: Item<bool> = Item::new("ibc_hooks");
+/// Donations counted by the default counter, not including increments from remote chains.
+pub const COUNTED_DONATIONS: Item<u64> = Item::new("counted_donations");
+/// Part of `COUNTED_DONATIONS` already sent over each channel.
+#[cfg(feature = "ibc")]
+pub const SYNCED: Map<&str, u64> = Map::new("synced");
+
+/// Transfer to a remote parent waiting for its outcome.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PendingTransfer {
+    pub channel: String,
+    pub counter_id: String,
+    pub parent: Addr,
+    pub amount: Coin,
 }
 
-pub const PARENT_DONATION: Item<ParentDonation> = Item::new("parent_donation");
-pub const STATE: Item<State> = Item::new("state");
-pub const OWNER: Item<Addr> = Item::new("owner");
\ No newline at end of file
+/// Transfers sent in the current transaction, in order, until their replies assign them
+/// sequence numbers.
+#[cfg(feature = "ibc")]
+pub const UNSEQUENCED_TRANSFERS: Item<Vec<PendingTransfer>> = Item::new("unsequenced_transfers");
+/// Transfers to remote parents by channel and sequence number.
+pub const PENDING_TRANSFERS: Map<(&str, u64), PendingTransfer> = Map::new("pending_transfers");
+
+pub const RECEIPT: Item<Receipt> = Item::new("receipt");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Proposal {
+    pub proposer: Addr,
+    pub recipient: Addr,
+    pub amount: Vec<Coin>,
+    pub description: String,
+    /// Voting weights are taken as of this height.
+    pub start_height: u64,
+    pub expires: Expiration,
+    /// Only `Open` or `Executed`, whether an open proposal passed is worked out from the votes.
+    pub status: ProposalStatus,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub total_weight: Uint128,
+    pub yes: Uint128,
+    pub no: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Ballot {
+    pub yes: bool,
+    pub weight: Uint128,
+}
+
+pub const GOVERNANCE: Item<GovernanceConfig> = Item::new("governance");
+/// What each donor donated to the default counter in its minimal donation denom.
+pub const VOTING_WEIGHT: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "voting_weight",
+    "voting_weight__checkpoints",
+    "voting_weight__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_WEIGHT: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_weight",
+    "total_weight__checkpoints",
+    "total_weight__changelog",
+    Strategy::EveryBlock,
+);
+pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+pub const BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("ballots");
+
+/// Embedded multisig owning the default counter, whose owner is then the contract itself.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Multisig {
+    pub members: Vec<Addr>,
+    pub threshold: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OwnerAction {
+    pub proposer: Addr,
+    pub action: ExecMsg,
+    pub approvals: Vec<Addr>,
+    pub executed: bool,
+}
+
+pub const MULTISIG: Item<Multisig> = Item::new("multisig");
+pub const OWNER_ACTION_COUNT: Item<u64> = Item::new("owner_action_count");
+pub const OWNER_ACTIONS: Map<u64, OwnerAction> = Map::new("owner_actions");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Subscription {
+    pub donor: Addr,
+    pub amount: Coin,
+    /// Seconds between donations.
+    pub period: u64,
+    /// Donations left to make.
+    pub remaining: u32,
+    pub next_due: Timestamp,
+}
+
+pub const SUBSCRIPTION_COUNT: Item<u64> = Item::new("subscription_count");
+pub const SUBSCRIPTIONS: Map<u64, Subscription> = Map::new("subscriptions");
+/// Active subscriptions keyed by when they are next due, in nanoseconds, and their id.
+pub const SUBSCRIPTION_QUEUE: Map<(u64, u64), Empty> = Map::new("subscription_queue");
+/// Subscription deposits not donated yet, kept out of the default counter balance.
+pub const ESCROWED: Item<Vec<Coin>> = Item::new("escrowed");
+
+/// Block of the last forward of a counter to its parent, or of setting the parent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ForwardTime {
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+/// Start of the current parent donating interval by counter id.
+pub const LAST_FORWARD: Map<&str, ForwardTime> = Map::new("last_forward");
+
+/// Part of a flushed forward paid to the caller. Set if forwards wait for `FlushToParent`.
+pub const FLUSH_BOUNTY: Item<Decimal> = Item::new("flush_bounty");
+/// Counters, by id, whose forward to their parent waits for `FlushToParent`.
+pub const PENDING_FORWARDS: Map<&str, Empty> = Map::new("pending_forwards");
+/// Flush bounties paid to callers of `FlushToParent`. Only used to check the accounting.
+pub const BOUNTIES_PAID: Item<Vec<Coin>> = Item::new("bounties_paid");
@@ -0,0 +1,181 @@
+//! Transfers to parents on other chains. They are sent as ICS-20 `MsgTransfer`s with an
+//! ibc-hooks callback memo, get their sequence number in `reply`, and complete in `sudo` once
+//! ibc-hooks reports the acknowledgement or timeout.
+
+use cosmwasm_std::{
+    Coin, CosmosMsg, DepsMut, Env, Reply, Response, StdError, StdResult, SubMsg, Timestamp,
+};
+use prost::Message;
+
+use crate::{
+    contract::{add_coins, record_forward},
+    error::ContractError,
+    events,
+    msg::{IbcLifecycleComplete, RemoteParent, SudoMsg},
+    proto::{MsgTransfer, MsgTransferResponse, ProtoCoin, MSG_TRANSFER_TYPE_URL},
+    state::{
+        ParentDonation, PendingTransfer, COUNTERS, DEFAULT_COUNTER_ID, FORWARDED, NAMED_BALANCE,
+        PENDING_TRANSFERS, UNSEQUENCED_TRANSFERS,
+    },
+};
+
+pub const TRANSFER_REPLY_ID: u64 = 1;
+
+/// ICS-20 transfer of `amount` to `to_address` over `channel`, as a protobuf encoded
+/// `MsgTransfer`. Unlike `IbcMsg::Transfer` it carries an `ibc_callback` memo, which makes
+/// ibc-hooks report the outcome of the transfer to `sudo`.
+pub fn transfer_msg(
+    env: &Env,
+    channel: &str,
+    to_address: &str,
+    amount: &Coin,
+    timeout: Timestamp,
+) -> CosmosMsg {
+    let transfer = MsgTransfer {
+        source_port: "transfer".to_owned(),
+        source_channel: channel.to_owned(),
+        token: Some(ProtoCoin {
+            denom: amount.denom.clone(),
+            amount: amount.amount.to_string(),
+        }),
+        sender: env.contract.address.to_string(),
+        receiver: to_address.to_owned(),
+        timeout_height: None,
+        timeout_timestamp: timeout.nanos(),
+        memo: format!(r#"{{"ibc_callback":"{}"}}"#, env.contract.address),
+    };
+
+    CosmosMsg::Stargate {
+        type_url: MSG_TRANSFER_TYPE_URL.to_owned(),
+        value: transfer.encode_to_vec().into(),
+    }
+}
+
+/// Reads the sequence from a protobuf encoded `MsgTransferResponse`.
+pub fn parse_transfer_sequence(data: &[u8]) -> Option<u64> {
+    MsgTransferResponse::decode(data)
+        .ok()
+        .map(|resp| resp.sequence)
+}
+
+/// Sends `funds` of counter `counter_id` to its remote parent, one ICS-20 transfer per coin.
+/// Each transfer waits in `UNSEQUENCED_TRANSFERS` until `reply` learns its sequence number.
+pub fn transfer_to_parent(
+    deps: DepsMut,
+    env: &Env,
+    counter_id: &str,
+    parent: &ParentDonation,
+    remote: &RemoteParent,
+    funds: Vec<Coin>,
+) -> StdResult<Vec<SubMsg>> {
+    let mut unsequenced = UNSEQUENCED_TRANSFERS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let mut msgs = vec![];
+    for amount in funds.into_iter().filter(|coin| !coin.amount.is_zero()) {
+        let transfer = transfer_msg(
+            env,
+            &remote.channel,
+            parent.address.as_str(),
+            &amount,
+            env.block.time.plus_seconds(remote.timeout),
+        );
+        msgs.push(SubMsg::reply_on_success(transfer, TRANSFER_REPLY_ID));
+
+        unsequenced.push(PendingTransfer {
+            channel: remote.channel.clone(),
+            counter_id: counter_id.to_owned(),
+            parent: parent.address.clone(),
+            amount,
+        });
+    }
+    UNSEQUENCED_TRANSFERS.save(deps.storage, &unsequenced)?;
+
+    Ok(msgs)
+}
+
+/// Assigns the next transfer sent to a remote parent its sequence number, so `sudo` can find
+/// it once it completes.
+pub fn reply(deps: DepsMut, reply: Reply) -> Result<Response, ContractError> {
+    if reply.id != TRANSFER_REPLY_ID {
+        return Err(ContractError::UnknownReply { id: reply.id });
+    }
+
+    let data = reply
+        .result
+        .into_result()
+        .map_err(StdError::generic_err)?
+        .data
+        .unwrap_or_default();
+    let sequence =
+        parse_transfer_sequence(&data).ok_or(ContractError::InvalidTransferResponse {})?;
+
+    let mut unsequenced = UNSEQUENCED_TRANSFERS.load(deps.storage)?;
+    if unsequenced.is_empty() {
+        return Err(StdError::not_found("PendingTransfer").into());
+    }
+    let transfer = unsequenced.remove(0);
+    if unsequenced.is_empty() {
+        UNSEQUENCED_TRANSFERS.remove(deps.storage);
+    } else {
+        UNSEQUENCED_TRANSFERS.save(deps.storage, &unsequenced)?;
+    }
+
+    PENDING_TRANSFERS.save(deps.storage, (&transfer.channel, sequence), &transfer)?;
+
+    let resp = Response::new()
+        .add_attribute("action", "transfer_to_parent")
+        .add_attribute("channel", transfer.channel)
+        .add_attribute("sequence", sequence.to_string());
+    Ok(resp)
+}
+
+/// Records a completed transfer to a remote parent. Failed and timed out transfers are
+/// refunded by the transfer module, so their funds go back to the counter which sent them.
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    let SudoMsg::IbcLifecycleComplete(complete) = msg;
+    let (channel, sequence, success) = match complete {
+        IbcLifecycleComplete::IbcAck {
+            channel,
+            sequence,
+            success,
+            ..
+        } => (channel, sequence, success),
+        IbcLifecycleComplete::IbcTimeout { channel, sequence } => (channel, sequence, false),
+    };
+
+    let transfer = PENDING_TRANSFERS.load(deps.storage, (&channel, sequence))?;
+    PENDING_TRANSFERS.remove(deps.storage, (&channel, sequence));
+
+    let mut resp = Response::new()
+        .add_attribute("action", "parent_transfer_complete")
+        .add_attribute("channel", channel)
+        .add_attribute("sequence", sequence.to_string())
+        .add_attribute("success", success.to_string());
+
+    let amount = vec![transfer.amount];
+    if success {
+        record_forward(deps, &env, &transfer.parent, &amount)?;
+        return Ok(resp);
+    }
+
+    FORWARDED.update(deps.storage, &transfer.parent, |stats| -> StdResult<_> {
+        let mut stats = stats.unwrap_or_default();
+        add_coins(&mut stats.refunded, &amount);
+        Ok(stats)
+    })?;
+
+    // The default counter owns whatever isn't in named counters, refunds included
+    if transfer.counter_id != DEFAULT_COUNTER_ID {
+        let mut counter = COUNTERS.load(deps.storage, &transfer.counter_id)?;
+        add_coins(&mut counter.balance, &amount);
+        COUNTERS.save(deps.storage, &transfer.counter_id, &counter)?;
+
+        let mut named = NAMED_BALANCE.may_load(deps.storage)?.unwrap_or_default();
+        add_coins(&mut named, &amount);
+        NAMED_BALANCE.save(deps.storage, &named)?;
+    }
+
+    resp = resp.add_event(events::parent_refund_event(&transfer.parent, &amount));
+    Ok(resp)
+}